@@ -0,0 +1,405 @@
+//! On-disk cache of parsed files, used to skip re-parsing files whose content did not change
+//!
+//! The cache stores, for each parsed file, the hash of its content along with the parsed namespace.
+//! The namespace is stored with all the information needed to rebuild it (imports, comments, options, ...),
+//! unlike the descriptors output that only keeps what protobuf.js needs.
+//!
+//! See [crate::parser::Parser::load_cache] and [crate::parser::Parser::save_cache]
+
+use crate::{
+    comment::Comment,
+    field::{Field, FieldRule},
+    import::Import,
+    message::Message,
+    metadata::{Metadata, ProtoOption},
+    namespace::Namespace,
+    oneof::Oneof,
+    r#enum::Enum,
+    r#type::Type,
+    reserved::Reserved,
+    service::{Rpc, Service},
+    syntax::Syntax,
+};
+use linked_hash_map::LinkedHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    iter::FromIterator,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Returns the hash of a file content.
+/// The hash is saved on disk, so it uses [FNV-1a] rather than the std hasher, whose algorithm may change between releases
+///
+/// [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/index.html#FNV-1a
+pub fn content_hash(content: &str) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+    content.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// The version of the cache format, caches saved with another version are ignored.
+/// It must be bumped whenever the parsed representation changes (e.g the format of the options)
+pub const CACHE_VERSION: u32 = 5;
+
+/// The content of a cache file
+#[derive(Serialize, Deserialize, Default)]
+pub struct Cache {
+    /// The version of the cache format, see [CACHE_VERSION]
+    #[serde(default)]
+    pub version: u32,
+
+    /// The options the files were parsed with, the cache is ignored when they differ from the current ones
+    #[serde(default)]
+    pub options: ParseOptions,
+
+    /// A map of file path => cached file
+    pub files: HashMap<PathBuf, CachedFile>,
+}
+
+/// The parser options that change the namespace parsed from a file
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct ParseOptions {
+    pub strict: bool,
+    pub forbid_required: bool,
+    pub collect_floating_comments: bool,
+}
+
+/// A cached parsed file
+#[derive(Serialize, Deserialize)]
+pub struct CachedFile {
+    /// The hash of the file content
+    pub hash: u64,
+
+    /// The namespace parsed from the file
+    pub namespace: CachedNamespace,
+}
+
+impl CachedFile {
+    /// Returns a new cached file
+    pub fn new(hash: u64, ns: &Namespace) -> Self {
+        Self {
+            hash,
+            namespace: CachedNamespace::from(ns),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CachedNamespace {
+    path: Vec<String>,
+    imports: Vec<Import>,
+    syntax: Syntax,
+    nested: BTreeMap<String, CachedNamespace>,
+    services: LinkedHashMap<String, CachedService>,
+    types: LinkedHashMap<String, CachedType>,
+}
+
+impl From<&Namespace> for CachedNamespace {
+    fn from(ns: &Namespace) -> Self {
+        Self {
+            path: ns.path.clone(),
+            imports: ns.imports.iter().cloned().collect(),
+            syntax: ns.syntax,
+            nested: map_values(ns.nested.iter()),
+            services: map_values(ns.services.iter()),
+            types: map_values(ns.types.iter()),
+        }
+    }
+}
+
+impl CachedNamespace {
+    /// Returns the namespace defined in the given file
+    pub fn into_namespace(self, file_path: &Rc<Path>) -> Namespace {
+        Namespace {
+            path: self.path,
+            imports: self.imports.into_iter().collect(),
+            syntax: self.syntax,
+            nested: self
+                .nested
+                .into_iter()
+                .map(|(name, child)| (name, child.into_namespace(file_path)))
+                .collect(),
+            services: self
+                .services
+                .into_iter()
+                .map(|(name, service)| (name, service.into_service(file_path)))
+                .collect(),
+            types: self
+                .types
+                .into_iter()
+                .map(|(name, t)| (name, t.into_type(file_path)))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedType {
+    Message(CachedMessage),
+    Enum(CachedEnum),
+}
+
+impl From<&Type> for CachedType {
+    fn from(t: &Type) -> Self {
+        match t {
+            Type::Message(msg) => Self::Message(CachedMessage::from(msg)),
+            Type::Enum(e) => Self::Enum(CachedEnum::from(e)),
+        }
+    }
+}
+
+impl CachedType {
+    fn into_type(self, file_path: &Rc<Path>) -> Type {
+        match self {
+            Self::Message(msg) => Type::Message(msg.into_message(file_path)),
+            Self::Enum(e) => Type::Enum(e.into_enum(file_path)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedMessage {
+    oneofs: LinkedHashMap<String, CachedOneof>,
+    fields: LinkedHashMap<String, CachedField>,
+    reserved: Vec<Reserved>,
+    nested: LinkedHashMap<String, CachedType>,
+    #[serde(default)]
+    floating_comments: Vec<Comment>,
+    md: CachedMetadata,
+}
+
+impl From<&Message> for CachedMessage {
+    fn from(msg: &Message) -> Self {
+        Self {
+            oneofs: map_values(msg.oneofs.iter()),
+            fields: map_values(msg.fields.iter()),
+            reserved: msg.reserved.clone(),
+            nested: map_values(msg.nested.iter()),
+            floating_comments: msg.floating_comments.clone(),
+            md: CachedMetadata::from(&msg.md),
+        }
+    }
+}
+
+impl CachedMessage {
+    fn into_message(self, file_path: &Rc<Path>) -> Message {
+        let mut msg = Message::new(self.md.into_metadata(file_path));
+        msg.reserved = self.reserved;
+        msg.floating_comments = self.floating_comments;
+        msg.oneofs = self
+            .oneofs
+            .into_iter()
+            .map(|(name, oneof)| (name, oneof.into_oneof(file_path)))
+            .collect();
+        msg.fields = self
+            .fields
+            .into_iter()
+            .map(|(name, field)| (name, field.into_field(file_path)))
+            .collect();
+        msg.nested = self
+            .nested
+            .into_iter()
+            .map(|(name, t)| (name, t.into_type(file_path)))
+            .collect();
+        msg
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedEnum {
+    values: LinkedHashMap<String, i32>,
+    reserved: Vec<Reserved>,
+    #[serde(default)]
+    values_md: LinkedHashMap<String, CachedMetadata>,
+    md: CachedMetadata,
+}
+
+impl From<&Enum> for CachedEnum {
+    fn from(e: &Enum) -> Self {
+        Self {
+            values: e.values.clone(),
+            reserved: e.reserved.clone(),
+            values_md: map_values(e.values_md.iter()),
+            md: CachedMetadata::from(&e.md),
+        }
+    }
+}
+
+impl CachedEnum {
+    fn into_enum(self, file_path: &Rc<Path>) -> Enum {
+        let mut e = Enum::new(self.md.into_metadata(file_path));
+        e.values = self.values;
+        e.reserved = self.reserved;
+        e.values_md = self
+            .values_md
+            .into_iter()
+            .map(|(name, md)| (name, md.into_metadata(file_path)))
+            .collect();
+        e
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedField {
+    type_name: String,
+    id: u32,
+    key_type: Option<String>,
+    rule: Option<FieldRule>,
+    oneof_name: Option<String>,
+    md: CachedMetadata,
+}
+
+impl From<&Field> for CachedField {
+    fn from(field: &Field) -> Self {
+        Self {
+            type_name: field.type_name.borrow().to_string(),
+            id: field.id,
+            key_type: field.key_type.clone(),
+            rule: field.rule,
+            oneof_name: field.oneof_name.clone(),
+            md: CachedMetadata::from(&field.md),
+        }
+    }
+}
+
+impl CachedField {
+    fn into_field(self, file_path: &Rc<Path>) -> Field {
+        let md = self.md.into_metadata(file_path);
+        let mut field = Field::new(self.id, self.type_name, self.rule, self.key_type, md);
+        field.oneof_name = self.oneof_name;
+        field
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedOneof {
+    values: Vec<String>,
+    md: CachedMetadata,
+}
+
+impl From<&Oneof> for CachedOneof {
+    fn from(oneof: &Oneof) -> Self {
+        Self {
+            values: oneof.values.clone(),
+            md: CachedMetadata::from(&oneof.md),
+        }
+    }
+}
+
+impl CachedOneof {
+    fn into_oneof(self, file_path: &Rc<Path>) -> Oneof {
+        let mut oneof = Oneof::new(self.md.into_metadata(file_path));
+        oneof.values = self.values;
+        oneof
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedService {
+    methods: LinkedHashMap<String, CachedRpc>,
+    md: CachedMetadata,
+}
+
+impl From<&Service> for CachedService {
+    fn from(service: &Service) -> Self {
+        Self {
+            methods: map_values(service.methods.iter()),
+            md: CachedMetadata::from(&service.md),
+        }
+    }
+}
+
+impl CachedService {
+    fn into_service(self, file_path: &Rc<Path>) -> Service {
+        let mut service = Service::new(self.md.into_metadata(file_path));
+        for (name, rpc) in self.methods {
+            service.add_rpc(name, rpc.into_rpc(file_path));
+        }
+        service
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedRpc {
+    request_type: String,
+    request_stream: bool,
+    response_type: String,
+    response_stream: bool,
+    md: CachedMetadata,
+}
+
+impl From<&Rpc> for CachedRpc {
+    fn from(rpc: &Rpc) -> Self {
+        Self {
+            request_type: rpc.request_type.borrow().to_string(),
+            request_stream: rpc.request_stream,
+            response_type: rpc.response_type.borrow().to_string(),
+            response_stream: rpc.response_stream,
+            md: CachedMetadata::from(&rpc.md),
+        }
+    }
+}
+
+impl CachedRpc {
+    fn into_rpc(self, file_path: &Rc<Path>) -> Rpc {
+        Rpc::new(
+            self.request_type,
+            self.request_stream,
+            self.response_type,
+            self.response_stream,
+            self.md.into_metadata(file_path),
+        )
+    }
+}
+
+/// The metadata of a cached object, the file path is restored from the cached file path
+#[derive(Serialize, Deserialize)]
+struct CachedMetadata {
+    options: Vec<ProtoOption>,
+    comment: Option<Comment>,
+    line: usize,
+}
+
+impl From<&Metadata> for CachedMetadata {
+    fn from(md: &Metadata) -> Self {
+        Self {
+            options: md.options.clone(),
+            comment: md.comment.clone(),
+            line: md.line,
+        }
+    }
+}
+
+impl CachedMetadata {
+    fn into_metadata(self, file_path: &Rc<Path>) -> Metadata {
+        let mut md = Metadata::new(file_path.clone(), self.comment, self.line);
+        md.options = self.options;
+        md
+    }
+}
+
+/// Convert the values of the given (name, value) iterator into their cached representation
+fn map_values<'a, T: 'a, C, M>(iter: impl Iterator<Item = (&'a String, &'a T)>) -> M
+where
+    C: From<&'a T>,
+    M: FromIterator<(String, C)>,
+{
+    iter.map(|(name, v)| (name.clone(), C::from(v))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::content_hash;
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(content_hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+}
@@ -1,13 +1,18 @@
+use serde::{Deserialize, Serialize};
+
 /// Comment represents a [proto comment]
 ///
 /// [proto comment]: https://developers.google.com/protocol-buffers/docs/proto#adding_comments
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum CommentKind {
+    /// A block comment: /* ... */
     StarSlash,
+
+    /// One or more consecutive line comments: // ...
     DoubleSlash,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Comment {
     pub kind: CommentKind,
     pub text: String,
@@ -32,4 +37,44 @@ impl Comment {
             end_line,
         }
     }
+
+    /// Returns the annotation tags (e.g `@example "abc"`) found in the comment as (tag, value) tuples.
+    /// Tags must start a line, and the value is the rest of the line
+    pub fn tags(&self) -> Vec<(String, String)> {
+        self.text
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_start().strip_prefix('@')?;
+                let (tag, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+                if tag.is_empty() {
+                    return None;
+                }
+
+                Some((tag.to_string(), value.trim().to_string()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Comment;
+
+    #[test]
+    fn test_tags() {
+        let comment = Comment::double_slash(
+            " The user name\n @example \"abc\"\n @since v2\n @deprecated".to_string(),
+            1,
+            4,
+        );
+
+        assert_eq!(
+            comment.tags(),
+            vec![
+                ("example".to_string(), "\"abc\"".to_string()),
+                ("since".to_string(), "v2".to_string()),
+                ("deprecated".to_string(), "".to_string()),
+            ]
+        );
+    }
 }
@@ -1,14 +1,22 @@
+use linked_hash_map::LinkedHashMap;
 use serde::Serialize;
-use std::collections::HashMap;
 
-use crate::metadata::Metadata;
+use crate::{metadata::Metadata, reserved::Reserved};
 
 /// Enum defines a proto [emum]
 /// [enum]: https://developers.google.com/protocol-buffers/docs/proto3#enum
 #[derive(Debug, Serialize)]
 pub struct Enum {
-    /// a map of name => field id
-    pub values: HashMap<String, i32>,
+    /// a map of name => field id, in declaration order
+    pub values: LinkedHashMap<String, i32>,
+
+    /// The list of reserved value ranges and names
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reserved: Vec<Reserved>,
+
+    /// a map of name => metadata (e.g comment) of the values that have one
+    #[serde(skip_serializing)]
+    pub values_md: LinkedHashMap<String, Metadata>,
 
     /// metadata associated to the Enum
     #[serde(skip_serializing)]
@@ -19,7 +27,9 @@ impl Enum {
     /// Rerturns a new Enum
     pub fn new(md: Metadata) -> Self {
         Self {
-            values: HashMap::new(),
+            values: LinkedHashMap::new(),
+            reserved: Vec::new(),
+            values_md: LinkedHashMap::new(),
             md,
         }
     }
@@ -28,4 +38,31 @@ impl Enum {
     pub fn insert(&mut self, key: String, id: i32) {
         self.values.insert(key, id);
     }
+
+    /// Set the metadata of the given value
+    pub fn set_value_metadata(&mut self, key: String, md: Metadata) {
+        self.values_md.insert(key, md);
+    }
+
+    /// Returns the comment text of the given value if any
+    pub fn value_comment(&self, key: &str) -> Option<&str> {
+        self.values_md.get(key).and_then(Metadata::comment_text)
+    }
+
+    /// Add reserved value ranges or names
+    pub fn add_reserved(&mut self, reserved: Vec<Reserved>) {
+        self.reserved.extend(reserved);
+    }
+
+    /// Returns the name of the first value that uses a reserved number or name if any
+    pub fn find_reserved_value(&self) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(name, id)| {
+                self.reserved
+                    .iter()
+                    .any(|r| r.contains_name(name) || r.contains_id(**id))
+            })
+            .map(|(name, _)| name.as_str())
+    }
 }
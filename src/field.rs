@@ -1,12 +1,44 @@
 use derive_more::Display;
-use serde::Serialize;
-use std::cell::RefCell;
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, ops::RangeInclusive};
 
-use crate::metadata::Metadata;
+use crate::{metadata::Metadata, validate::FieldConstraints};
+
+/// Field numbers [reserved] for the Protocol Buffers implementation
+///
+/// [reserved]: https://developers.google.com/protocol-buffers/docs/proto3#assigning_field_numbers
+pub const RESERVED_FIELD_NUMBERS: RangeInclusive<u32> = 19000..=19999;
+
+/// The largest field number allowed (2^29 - 1)
+pub const MAX_FIELD_NUMBER: u32 = 536_870_911;
+
+/// Returns true if the given id can be used as a field number
+pub fn is_valid_field_number(id: u32) -> bool {
+    id != 0 && id <= MAX_FIELD_NUMBER && !RESERVED_FIELD_NUMBERS.contains(&id)
+}
+
+/// Returns the default JSON name of a field, as computed by protoc (e.g foo_bar => fooBar)
+pub fn default_json_name(name: &str) -> String {
+    let mut json_name = String::with_capacity(name.len());
+    let mut capitalize_next = false;
+
+    for c in name.chars() {
+        match c {
+            '_' => capitalize_next = true,
+            c if capitalize_next => {
+                json_name.push(c.to_ascii_uppercase());
+                capitalize_next = false;
+            }
+            c => json_name.push(c),
+        }
+    }
+
+    json_name
+}
 
 /// FieldRule represents a proto [field rule]
 /// [field rule]: https://developers.google.com/protocol-buffers/docs/proto#specifying_field_rules
-#[derive(Display, Debug, Serialize, PartialEq)]
+#[derive(Display, Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum FieldRule {
     #[display(fmt = "repeated")]
@@ -19,6 +51,21 @@ pub enum FieldRule {
     Required,
 }
 
+/// JsType represents the [jstype] option of 64-bit integer fields
+///
+/// [jstype]: https://github.com/protocolbuffers/protobuf/blob/master/src/google/protobuf/descriptor.proto
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsType {
+    /// JS_NORMAL: use the default type
+    Normal,
+
+    /// JS_STRING: the value is represented as a string
+    String,
+
+    /// JS_NUMBER: the value is represented as a number
+    Number,
+}
+
 /// Field represents a proto message [field]
 /// [field]: https://developers.google.com/protocol-buffers/docs/proto#specifying_field_types
 #[derive(Serialize, Debug)]
@@ -38,6 +85,10 @@ pub struct Field {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rule: Option<FieldRule>,
 
+    /// The name of the oneof this field belongs to if any
+    #[serde(rename = "oneofName", skip_serializing_if = "Option::is_none")]
+    pub oneof_name: Option<String>,
+
     /// metadata associated to the Enum
     #[serde(skip_serializing)]
     pub md: Metadata,
@@ -57,7 +108,36 @@ impl Field {
             type_name: RefCell::new(type_name),
             rule,
             key_type,
+            oneof_name: None,
             md,
         }
     }
+
+    /// Returns the JSON name of the field, either set with the json_name option or derived from its name
+    pub fn json_name(&self, name: &str) -> String {
+        match self.md.option_value("json_name") {
+            Some(json_name) => json_name.to_string(),
+            None => default_json_name(name),
+        }
+    }
+
+    /// Returns the proto2 default value of the field if it's defined (e.g `10` in `[default = 10]`)
+    pub fn default_value(&self) -> Option<&str> {
+        self.md.option_value("default")
+    }
+
+    /// Returns the protoc-gen-validate constraints of the field if any, see [FieldConstraints]
+    pub fn constraints(&self) -> Option<FieldConstraints> {
+        FieldConstraints::from_options(&self.md.options)
+    }
+
+    /// Returns the jstype option of the field if it's defined
+    pub fn js_type(&self) -> Option<JsType> {
+        match self.md.option_value("jstype")? {
+            "JS_NORMAL" => Some(JsType::Normal),
+            "JS_STRING" => Some(JsType::String),
+            "JS_NUMBER" => Some(JsType::Number),
+            _ => None,
+        }
+    }
 }
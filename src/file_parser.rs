@@ -1,5 +1,6 @@
 use crate::{
-    field::{Field, FieldRule},
+    comment::Comment,
+    field::{is_valid_field_number, Field, FieldRule, MAX_FIELD_NUMBER},
     import::Import,
     into_path::IntoPath,
     message::Message,
@@ -8,11 +9,13 @@ use crate::{
     oneof::Oneof,
     parse_error::{ParseError, ParseErrorWithPosition, TokenError},
     r#enum::Enum,
+    reserved::Reserved,
     service::{Rpc, Service},
+    syntax::Syntax,
     token::Token,
     tokenizer::Tokenizer,
 };
-use std::{path::Path, rc::Rc, vec};
+use std::{convert::TryFrom, path::Path, rc::Rc, vec};
 
 /// FileParser parse a single file into a namespace
 pub struct FileParser<I: Iterator> {
@@ -22,11 +25,17 @@ pub struct FileParser<I: Iterator> {
     /// The tokenizer used to parse the file
     tokenizer: Tokenizer<I>,
 
-    // Peeked token
-    peeked: Option<Result<Token, TokenError>>,
-
     /// The namespace that will be populated as we parse the file
     namespace: Namespace,
+
+    /// When set, relaxed statements (e.g extra ";") are rejected instead of being skipped
+    strict: bool,
+
+    /// When set, proto2 required fields are rejected
+    forbid_required: bool,
+
+    /// The names of the messages being parsed, from the outermost to the innermost
+    message_path: Vec<String>,
 }
 
 impl<I: Iterator<Item = char>> FileParser<I> {
@@ -35,8 +44,49 @@ impl<I: Iterator<Item = char>> FileParser<I> {
         Self {
             file_path: file_path.into(),
             tokenizer: Tokenizer::new(iter),
-            peeked: None,
             namespace: Namespace::default(),
+            strict: false,
+            forbid_required: false,
+            message_path: Vec::new(),
+        }
+    }
+
+    /// Enable or disable strict mode
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Enable or disable the rejection of proto2 `required` fields with [ParseError::RequiredFieldForbidden]
+    pub fn set_forbid_required(&mut self, forbid_required: bool) {
+        self.forbid_required = forbid_required;
+    }
+
+    /// Enable or disable the collection of floating comments.
+    /// When set, comments inside a message that are neither leading nor trailing comments of a definition
+    /// are collected into [Message::floating_comments] instead of being dropped
+    pub fn set_collect_floating_comments(&mut self, enable: bool) {
+        self.tokenizer.floating_comments = if enable { Some(Vec::new()) } else { None };
+    }
+
+    /// Returns the floating comments collected after the given line, see [FileParser::set_collect_floating_comments]
+    fn take_floating_comments(&mut self, after_line: usize) -> Vec<Comment> {
+        if self.tokenizer.floating_comments.is_none() {
+            return Vec::new();
+        }
+
+        if let Some(comment) = self.tokenizer.comment.take() {
+            self.tokenizer.add_floating_comment(comment);
+        }
+
+        match self.tokenizer.floating_comments.as_mut() {
+            None => Vec::new(),
+            Some(floating_comments) => {
+                let (taken, remaining) = std::mem::take(floating_comments)
+                    .into_iter()
+                    .partition(|cmt| cmt.start_line > after_line);
+                *floating_comments = remaining;
+                taken
+            }
         }
     }
 
@@ -63,15 +113,17 @@ impl<I: Iterator<Item = char>> FileParser<I> {
                 }
                 Token::Syntax => {
                     let syntax = self.parse_syntax()?;
-                    if syntax != "proto3" && syntax != "proto2" {
-                        return Err(ParseError::ProtoSyntaxNotSupported(syntax));
-                    }
+                    self.namespace.syntax = Syntax::from_version(&syntax)
+                        .ok_or(ParseError::ProtoSyntaxNotSupported(syntax))?;
                 }
                 Token::Option => {
                     self.parse_option()?;
                 }
                 Token::Service => {
                     let (name, service) = self.parse_service()?;
+                    if self.namespace.services.contains_key(&name) {
+                        return Err(ParseError::DuplicateService(name));
+                    }
                     self.namespace.add_service(name, service);
                 }
                 Token::Message => {
@@ -79,26 +131,27 @@ impl<I: Iterator<Item = char>> FileParser<I> {
                     self.namespace.add_message(name, message);
                 }
                 Token::Extend => {
-                    self.parse_message()?;
+                    let (name, _) = self.parse_message()?;
+
+                    // extensions are not part of the namespace, they can't be dropped silently in strict mode
+                    if self.strict {
+                        return Err(ParseError::UnsupportedExtend(name));
+                    }
                 }
                 Token::Enum => {
                     let (name, enum_tuples) = self.parse_enum()?;
                     self.namespace.add_enum(name, enum_tuples);
                 }
                 // relax extra ;
-                Token::Semi => {}
+                Token::Semi if !self.strict => {}
 
                 token => return Err(ParseError::UnexpectedTopLevelToken(token)),
             }
         }
     }
 
-    /// Advance the iterator or take the peeked item
+    /// Advance the iterator
     fn next(&mut self) -> Result<Token, TokenError> {
-        if let Some(v) = self.peeked.take() {
-            return v;
-        }
-
         self.tokenizer.next()
     }
 
@@ -106,8 +159,6 @@ impl<I: Iterator<Item = char>> FileParser<I> {
         let comment = self.tokenizer.comment.take();
         let line = self.tokenizer.current_line();
 
-        assert!(self.peeked.is_none());
-
         match comment {
             // get leading_comments if any
             Some(cmt) if cmt.end_line == line - 1 => {
@@ -115,19 +166,27 @@ impl<I: Iterator<Item = char>> FileParser<I> {
             }
 
             // get trailing_comments if any
-            _ => {
-                // peek next value
-                self.peeked.replace(self.tokenizer.next());
-                let trailing_comment = match self.tokenizer.comment.as_ref() {
-                    Some(cmt) if cmt.start_line == line => self.tokenizer.comment.take(),
-                    _ => None,
-                };
+            comment => {
+                if let Some(comment) = comment {
+                    self.tokenizer.add_floating_comment(comment);
+                }
 
+                let trailing_comment = self.trailing_comment(line);
                 Metadata::new(self.file_path.clone(), trailing_comment, line)
             }
         }
     }
 
+    /// Returns the comment following the current token if it starts on the given line
+    fn trailing_comment(&mut self, line: usize) -> Option<Comment> {
+        // peek next value, errors are returned when the token is consumed
+        let _ = self.tokenizer.peek();
+        match self.tokenizer.trailing_comment.as_ref() {
+            Some(cmt) if cmt.start_line == line => self.tokenizer.trailing_comment.take(),
+            _ => None,
+        }
+    }
+
     /// Parse the [package] name
     /// For example:
     ///
@@ -155,14 +214,15 @@ impl<I: Iterator<Item = char>> FileParser<I> {
     ///
     /// [import]: https://developers.google.com/protocol-buffers/docs/proto3#importing_definitions
     fn parse_import(&mut self) -> Result<(), ParseError> {
+        let line = self.tokenizer.current_line();
         let import = match self.next()? {
             Token::Public => {
                 let str = self.next()?.into_quoted_string()?;
-                Import::Public(str.into())
+                Import::Public(str.into(), line)
             }
             token => {
                 let str = token.into_quoted_string()?;
-                Import::Internal(str.into())
+                Import::Internal(str.into(), line)
             }
         };
 
@@ -172,9 +232,8 @@ impl<I: Iterator<Item = char>> FileParser<I> {
     }
 
     /// Parse [syntax] statement
-    /// Note: We don't add this information to the namespace,
-    /// we only use the result here to validate that the proto syntax is supported     
-    ///    
+    /// Note: Files without syntax statement default to proto2
+    ///
     /// For example:
     ///
     /// ```proto
@@ -184,25 +243,123 @@ impl<I: Iterator<Item = char>> FileParser<I> {
     /// [syntax]: https://developers.google.com/protocol-buffers/docs/proto3#simple
     fn parse_syntax(&mut self) -> Result<String, ParseError> {
         self.expect_token(Token::Eq)?;
-        let version = self.read_quoted_string()?;
+        let version = match self.next()? {
+            Token::Identifier(version) => return Err(ParseError::UnquotedSyntax(version)),
+            token => token.into_quoted_string()?,
+        };
         self.expect_token(Token::Semi)?;
         Ok(version)
     }
 
     /// Parse [option] statement    
     /// Note: we currently simply parse an option as a list of identifiers
+    /// Extension names are normalized, so that `( .foo . bar )` is stored as `foo.bar`
+    ///
+    /// Keys of nested blocks are prefixed with the block's key, so that `key { a: 1 }`, `key: { a: 1 }`
+    /// and `key { a 1 }` are all stored as `key.a`, `1`.
+    /// The sub path of an extension is read as the key of its value, so that `(foo).a = { b: 1 }` is stored as
+    /// `foo`, `a.b`, `1`, the same as `(foo) = { a { b: 1 } }`
     ///
     /// [option]: https://developers.google.com/protocol-buffers/docs/proto3#options
     fn parse_option(&mut self) -> Result<Vec<String>, ParseError> {
+        self.parse_option_until(Token::Semi)
+    }
+
+    /// Parse an option up to the given end token, e.g `]` for the options of an enum value.
+    /// See [FileParser::parse_option]
+    fn parse_option_until(&mut self, end: Token) -> Result<Vec<String>, ParseError> {
         let mut values = Vec::new();
+
+        // the parenthesized extension name being read if any
+        let mut extension: Option<String> = None;
+
+        // the sub path following an extension name, e.g `a.b` in `(foo).a.b = 1`
+        let mut sub_path: Option<String> = None;
+
+        // the key of each open block, None for the outermost block (e.g `= { ... }`)
+        let mut blocks: Vec<Option<String>> = Vec::new();
+
+        // whether the next value read inside a block is a key
+        let mut expect_key = false;
+
         loop {
-            match self.next()? {
-                Token::Semi => break,
+            let token = self.next()?;
+            if token == end {
+                break;
+            }
+
+            let value = match token {
+                Token::Semi => {
+                    return Err(ParseError::UnexpectedToken {
+                        found: Token::Semi,
+                        expected: vec![end],
+                    })
+                }
                 Token::EOF => return Err(ParseError::EOF),
-                Token::Identifier(s) | Token::String(s) => {
-                    values.push(s);
+                Token::LParen => {
+                    extension = Some(String::new());
+                    continue;
+                }
+                Token::RParen => {
+                    if let Some(name) = extension.take() {
+                        values.push(name.trim_start_matches('.').to_string());
+                    }
+                    continue;
+                }
+                Token::LBrace => {
+                    // the value read before a nested block is the block's key
+                    let key = match blocks.is_empty() {
+                        true => sub_path.take(),
+                        false => values.pop(),
+                    };
+                    blocks.push(key);
+                    expect_key = true;
+                    continue;
+                }
+                Token::RBrace => {
+                    blocks.pop();
+                    expect_key = true;
+                    continue;
+                }
+                Token::Identifier(s) => match extension.as_mut() {
+                    Some(name) => {
+                        name.push_str(&s);
+                        continue;
+                    }
+                    None if blocks.is_empty() && s.starts_with('.') => {
+                        sub_path = Some(s[1..].to_string());
+                        continue;
+                    }
+                    None => s,
+                },
+                Token::String(s) => s,
+                Token::Eq
+                | Token::Colon
+                | Token::Comma
+                | Token::LBrack
+                | Token::RBrack
+                | Token::LAngle
+                | Token::Rangle => continue,
+                _ if !self.strict => continue,
+                found => {
+                    return Err(ParseError::UnexpectedToken {
+                        found,
+                        expected: vec![Token::Eq, Token::Semi],
+                    })
+                }
+            };
+
+            match blocks.last() {
+                Some(Some(prefix)) if expect_key => values.push(format!("{}.{}", prefix, value)),
+                Some(_) => values.push(value),
+                None => {
+                    values.extend(sub_path.take());
+                    values.push(value);
                 }
-                _ => {}
+            }
+
+            if !blocks.is_empty() {
+                expect_key = !expect_key;
             }
         }
 
@@ -225,6 +382,7 @@ impl<I: Iterator<Item = char>> FileParser<I> {
     fn parse_message(&mut self) -> Result<(String, Message), ParseError> {
         let message_name = self.read_identifier()?;
         self.expect_token(Token::LBrace)?;
+        self.message_path.push(message_name.clone());
 
         let mut message = Message::new(self.metadata());
         let mut oneof = None;
@@ -249,7 +407,8 @@ impl<I: Iterator<Item = char>> FileParser<I> {
                     message.add_nested_enum(name, enum_tuples);
                 }
                 Token::Reserved => {
-                    self.parse_reserved()?;
+                    let reserved = self.parse_reserved(MAX_FIELD_NUMBER as i32)?;
+                    message.add_reserved(reserved);
                 }
                 Token::Extensions => {
                     self.parse_extensions()?;
@@ -258,7 +417,10 @@ impl<I: Iterator<Item = char>> FileParser<I> {
                     message.md.add_option(self.parse_option()?);
                 }
                 Token::FieldRule(rule) => {
-                    let type_name = self.read_identifier()?;
+                    let type_name = match self.next()? {
+                        Token::Map => return Err(ParseError::RuleOnMapField(rule)),
+                        token => token.identifier()?,
+                    };
                     let (name, field) = self.parse_message_field(type_name, Some(rule), None)?;
                     message.add_field(name, field);
                 }
@@ -274,21 +436,49 @@ impl<I: Iterator<Item = char>> FileParser<I> {
                     message.add_field(name, field);
                 }
                 Token::Identifier(type_name) => {
-                    let (name, field) = self.parse_message_field(type_name, None, None)?;
+                    let (name, mut field) = self.parse_message_field(type_name, None, None)?;
 
-                    if let Some(ref mut oneof) = oneof {
-                        oneof.1.add_field_name(name.to_string())
+                    if let Some((ref oneof_name, ref mut oneof)) = oneof {
+                        oneof.add_field_name(name.to_string());
+                        field.oneof_name = Some(oneof_name.to_string());
                     }
 
                     message.add_field(name, field);
                 }
-                Token::Semi => {
+                Token::Semi if !self.strict => {
                     // relax extra ";"
                 }
                 token => return Err(ParseError::UnexpectedMessageToken(token)),
             }
         }
 
+        message.floating_comments = self.take_floating_comments(message.md.line);
+        let message_path = self
+            .namespace
+            .path
+            .iter()
+            .chain(self.message_path.iter())
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(".");
+        message.inline_map_entries(&message_path);
+        self.message_path.pop();
+
+        if let Some(name) = message.find_reserved_field() {
+            return Err(ParseError::UseOfReservedField(name.to_string()));
+        }
+
+        // protoc rejects JSON name collisions in proto3 files
+        if self.namespace.syntax == Syntax::Proto3 {
+            if let Some((first, second, json_name)) = message.find_json_name_collision() {
+                return Err(ParseError::JsonNameCollision {
+                    first: first.to_string(),
+                    second: second.to_string(),
+                    json_name,
+                });
+            }
+        }
+
         Ok((message_name, message))
     }
 
@@ -314,12 +504,18 @@ impl<I: Iterator<Item = char>> FileParser<I> {
                 Token::RBrace => {
                     break;
                 }
-                Token::Semi => {
+                Token::Semi if !self.strict => {
                     // relax extra ;
                 }
                 Token::Rpc => {
-                    let (name, rpc) = self.parse_rpc()?;
-                    service.add_rpc(name, rpc)
+                    let (method, rpc) = self.parse_rpc()?;
+                    if service.methods.contains_key(&method) {
+                        return Err(ParseError::DuplicateRpc {
+                            service: name,
+                            method,
+                        });
+                    }
+                    service.add_rpc(method, rpc)
                 }
                 Token::Option => {
                     self.parse_option()?;
@@ -357,7 +553,9 @@ impl<I: Iterator<Item = char>> FileParser<I> {
         };
 
         self.expect_token(Token::RParen)?;
-        self.expect_token(Token::Returns)?;
+        if self.next()? != Token::Returns {
+            return Err(ParseError::MissingRpcReturns(name));
+        }
         self.expect_token(Token::LParen)?;
 
         let (response_type, response_stream) = match self.next()? {
@@ -379,9 +577,9 @@ impl<I: Iterator<Item = char>> FileParser<I> {
                         break;
                     }
                     found => {
-                        return Err(ParseError::UnexpectedToken {
+                        return Err(ParseError::UnexpectedRpcStatement {
+                            method: name,
                             found,
-                            expected: vec![Token::Option, Token::RBrace],
                         })
                     }
                 }
@@ -421,7 +619,15 @@ impl<I: Iterator<Item = char>> FileParser<I> {
         rule: Option<FieldRule>,
         key_type: Option<String>,
     ) -> Result<(String, Field), ParseError> {
+        // keep the leading comment of the field aside while reading the field name and number,
+        // so that inline comments (e.g `foo = /* id */ 1`) don't replace it
+        let leading_comment = self.tokenizer.comment.take();
         let field_name = self.read_identifier()?;
+
+        if self.forbid_required && matches!(rule, Some(FieldRule::Required)) {
+            return Err(ParseError::RequiredFieldForbidden { field: field_name });
+        }
+
         self.expect_token(Token::Eq)?;
 
         let field_id = self
@@ -429,8 +635,18 @@ impl<I: Iterator<Item = char>> FileParser<I> {
             .parse::<u32>()
             .map_err(ParseError::ParseFieldId)?;
 
+        if !is_valid_field_number(field_id) {
+            return Err(ParseError::ReservedFieldNumber(field_id));
+        }
+
+        let inline_comment = std::mem::replace(&mut self.tokenizer.comment, leading_comment);
+        let trailing_comment = self.tokenizer.trailing_comment.take();
+        for comment in inline_comment.into_iter().chain(trailing_comment) {
+            self.tokenizer.add_floating_comment(comment);
+        }
+
         let mut md = self.metadata();
-        md.options = vec![self.parse_option()?];
+        md.add_option(self.parse_option()?);
 
         Ok((
             field_name,
@@ -458,23 +674,44 @@ impl<I: Iterator<Item = char>> FileParser<I> {
 
         loop {
             match self.next()? {
-                Token::RBrace => return Ok((enum_name, e)),
+                Token::RBrace => break,
                 Token::Identifier(key) => {
+                    let mut md = self.metadata();
                     self.expect_token(Token::Eq)?;
 
                     let val_str = self.read_identifier()?;
                     let val_str_trimmed = val_str.trim_start_matches("0x");
                     let radix = if val_str.eq(val_str_trimmed) { 10 } else { 16 };
 
-                    let value = i32::from_str_radix(val_str_trimmed, radix)
+                    // enum values are int32, values such as 0xFFFFFFFF are rejected rather than wrapped
+                    let value = i64::from_str_radix(val_str_trimmed, radix)
                         .map_err(ParseError::ParseEnumValue)?;
+                    let value = i32::try_from(value)
+                        .map_err(|_| ParseError::EnumValueOutOfRange(val_str.clone()))?;
 
-                    match self.next()? {
-                        Token::Semi => {}
-                        Token::LBrack => {
-                            self.tokenizer.skip_until_token(Token::RBrack)?;
-                            self.expect_token(Token::Semi)?;
+                    let mut token = self.next()?;
+                    if token == Token::LBrack {
+                        md.add_option(self.parse_option_until(Token::RBrack)?);
+                        token = self.next()?;
+                    }
+
+                    // attach the comment following the value on the same line, e.g `FOO = 1; // first`
+                    if token == Token::Semi {
+                        let trailing_comment = self.trailing_comment(md.line);
+                        if md.comment.is_none() {
+                            md.comment = trailing_comment;
                         }
+                    }
+
+                    e.insert(key.clone(), value);
+                    if md.comment.is_some() || !md.options.is_empty() {
+                        e.set_value_metadata(key, md);
+                    }
+
+                    match token {
+                        Token::Semi => {}
+                        // relax missing ";" after the last value
+                        Token::RBrace if !self.strict => break,
                         found => {
                             return Err(ParseError::UnexpectedToken {
                                 found,
@@ -482,14 +719,13 @@ impl<I: Iterator<Item = char>> FileParser<I> {
                             })
                         }
                     }
-
-                    e.insert(key, value);
                 }
                 Token::Option => {
                     self.parse_option()?;
                 }
                 Token::Reserved => {
-                    self.tokenizer.skip_until_token(Token::Semi)?;
+                    let reserved = self.parse_reserved(i32::MAX)?;
+                    e.add_reserved(reserved);
                 }
                 found => {
                     return Err(ParseError::UnexpectedToken {
@@ -499,24 +735,74 @@ impl<I: Iterator<Item = char>> FileParser<I> {
                 }
             }
         }
+
+        if let Some(name) = e.find_reserved_value() {
+            return Err(ParseError::UseOfReservedEnumValue(name.to_string()));
+        }
+
+        // proto3 enums must start with a zero value, used as the default value
+        if self.namespace.syntax == Syntax::Proto3 && e.values.values().next() != Some(&0) {
+            return Err(ParseError::Proto3EnumMissingZero(enum_name));
+        }
+
+        Ok((enum_name, e))
     }
 
-    /// Parse a message [reserved] fields
-    /// We currently do not parse reserved, we simply fast forward to the end of the statement
+    /// Parse a message or enum [reserved] fields
+    /// Returns the list of reserved ranges and names,
+    /// `max` is the value used for ranges ending with the max keyword
     /// For example:
     ///
     /// ```proto
-    /// reserved 2, 15, 9 to 11;
+    /// reserved 2, 15, 9 to 11, 40 to max;
+    /// reserved "foo", "bar";
     /// ```
     ///
     /// [reserved]: https://developers.google.com/protocol-buffers/docs/proto3#reserved
-    fn parse_reserved(&mut self) -> Result<(), ParseError> {
-        self.tokenizer.skip_until_token(Token::Semi)?;
-        Ok(())
+    fn parse_reserved(&mut self, max: i32) -> Result<Vec<Reserved>, ParseError> {
+        let mut reserved = Vec::new();
+
+        // the start of the range being parsed if any
+        let mut start = None;
+
+        loop {
+            match self.next()? {
+                Token::String(name) => reserved.push(Reserved::Name(name)),
+                Token::Identifier(to) if to == "to" && start.is_some() => {
+                    let start = start.take().unwrap();
+                    let end = match self.read_identifier()?.as_str() {
+                        "max" => max,
+                        value => parse_reserved_number(value)?,
+                    };
+                    reserved.push(Reserved::Range(start, end));
+                }
+                Token::Identifier(value) => {
+                    if let Some(start) = start.replace(parse_reserved_number(&value)?) {
+                        reserved.push(Reserved::Range(start, start));
+                    }
+                }
+                token @ (Token::Comma | Token::Semi) => {
+                    if let Some(start) = start.take() {
+                        reserved.push(Reserved::Range(start, start));
+                    }
+
+                    if token == Token::Semi {
+                        return Ok(reserved);
+                    }
+                }
+                found => {
+                    return Err(ParseError::UnexpectedToken {
+                        found,
+                        expected: vec![Token::Comma, Token::Semi],
+                    })
+                }
+            }
+        }
     }
 
     /// Parse a message [extension]
-    /// We currently do not parse extensions, we simply fast forward to the end of the statement
+    /// We currently do not store extensions, we simply fast forward to the end of the statement.
+    /// In strict mode, the ranges are validated instead
     /// For example:
     ///
     /// ```proto
@@ -525,16 +811,19 @@ impl<I: Iterator<Item = char>> FileParser<I> {
     ///
     /// [extension]: https://developers.google.com/protocol-buffers/docs/proto#extensions
     fn parse_extensions(&mut self) -> Result<(), ParseError> {
-        self.tokenizer.skip_until_token(Token::Semi)?;
-        Ok(())
-    }
+        if !self.strict {
+            self.tokenizer.skip_until_token(Token::Semi)?;
+            return Ok(());
+        }
 
-    /// Read a quoted string or fail with an error
-    fn read_quoted_string(&mut self) -> Result<String, ParseError> {
-        match self.next()? {
-            Token::String(v) => Ok(v),
-            token => Err(ParseError::UnexpectedString(token)),
+        // extensions ranges use the reserved ranges syntax, without names
+        for range in self.parse_reserved(MAX_FIELD_NUMBER as i32)? {
+            if let Reserved::Name(name) = range {
+                return Err(ParseError::UnexpectedString(Token::String(name)));
+            }
         }
+
+        Ok(())
     }
 
     /// Read a string identifier or fail with an error
@@ -555,9 +844,19 @@ impl<I: Iterator<Item = char>> FileParser<I> {
     }
 }
 
+/// Parse a reserved field number
+fn parse_reserved_number(value: &str) -> Result<i32, ParseError> {
+    value.parse().map_err(ParseError::ParseReservedRange)
+}
+
 #[cfg(test)]
 mod tests {
     use super::FileParser;
+    use crate::{
+        comment::CommentKind, field::FieldRule, parse_error::ParseError, r#type::Type,
+        reserved::Reserved, service::IdempotencyLevel, syntax::Syntax, token::Token,
+    };
+    use indoc::indoc;
     use std::path::PathBuf;
 
     #[test]
@@ -586,6 +885,961 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn it_should_preserve_comment_kind() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        /*
+         * # Foo
+         * Some *markdown*
+         */
+        message Foo {}
+
+        // Bar
+        message Bar {}
+        "#;
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let comment_kind = |name: &str| {
+            ns.types
+                .get(name)
+                .and_then(|t| t.as_message())
+                .and_then(|msg| msg.md.comment_kind())
+        };
+
+        assert_eq!(comment_kind("Foo"), Some(CommentKind::StarSlash));
+        assert_eq!(comment_kind("Bar"), Some(CommentKind::DoubleSlash));
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_keep_doc_comments_followed_by_a_separator(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        // Foo docs
+        // ---------
+        message Foo {}
+
+        // ---------
+        // Bar docs
+        message Bar {}
+        "#;
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let comment_text = |name: &str| {
+            ns.types
+                .get(name)
+                .and_then(|t| t.as_message())
+                .and_then(|msg| msg.md.comment_text())
+        };
+
+        assert_eq!(comment_text("Foo"), Some(" Foo docs"));
+        assert_eq!(comment_text("Bar"), Some(" Bar docs"));
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_relax_extra_semi_by_default() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        message Foo {
+            optional int32 foo = 1;;
+        };
+        service Bar {
+            rpc Baz(Foo) returns (Foo);;
+        }
+        "#;
+
+        let parser = FileParser::new(file_path, text.chars());
+        parser.parse()?;
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_reject_extra_semi_in_strict_mode() {
+        let file_path: PathBuf = "test.proto".into();
+
+        for (text, expected) in [
+            (
+                "message Foo {};",
+                ParseError::UnexpectedTopLevelToken(Token::Semi),
+            ),
+            (
+                "message Foo { optional int32 foo = 1;; }",
+                ParseError::UnexpectedMessageToken(Token::Semi),
+            ),
+            (
+                "service Bar { ; }",
+                ParseError::UnexpectedToken {
+                    found: Token::Semi,
+                    expected: vec![Token::RBrace, Token::Rpc, Token::Option],
+                },
+            ),
+        ] {
+            let mut parser = FileParser::new(file_path.clone(), text.chars());
+            parser.set_strict(true);
+            let error = parser
+                .parse()
+                .expect_err("strict mode should reject extra ;");
+            assert_eq!(error.0, expected);
+        }
+    }
+
+    #[test]
+    fn it_should_reject_skipped_tokens_in_strict_mode() {
+        let file_path: PathBuf = "test.proto".into();
+
+        for (text, expected) in [
+            (
+                "option (foo) = { message: 1 };",
+                ParseError::UnexpectedToken {
+                    found: Token::Message,
+                    expected: vec![Token::Eq, Token::Semi],
+                },
+            ),
+            (
+                "enum Foo { BAR = 0 [deprecated = true] }",
+                ParseError::UnexpectedToken {
+                    found: Token::RBrace,
+                    expected: vec![Token::Semi, Token::LBrack],
+                },
+            ),
+            (
+                "message Foo { extensions 100 to max, \"bar\"; }",
+                ParseError::UnexpectedString(Token::String("bar".to_string())),
+            ),
+            (
+                "message Foo { extensions 100 to 200 message; }",
+                ParseError::UnexpectedToken {
+                    found: Token::Message,
+                    expected: vec![Token::Comma, Token::Semi],
+                },
+            ),
+            (
+                "extend Foo { int32 bar = 100; }",
+                ParseError::UnsupportedExtend("Foo".to_string()),
+            ),
+        ] {
+            let mut parser = FileParser::new(file_path.clone(), text.chars());
+            parser.set_strict(true);
+            let error = parser
+                .parse()
+                .expect_err("strict mode should reject the statement");
+            assert_eq!(error.0, expected, "{}", text);
+        }
+
+        let text = indoc! {r#"
+        option (foo) = { bar: [1, 2] };
+        message Foo {
+          extensions 100 to 199, 500 to max;
+        }
+        enum Status {
+          UNKNOWN = 0 [deprecated = true];
+        }
+        "#};
+
+        let mut parser = FileParser::new(file_path, text.chars());
+        parser.set_strict(true);
+        let ns = parser
+            .parse()
+            .expect("strict mode should accept the statements");
+        match &ns.types["Status"] {
+            Type::Enum(e) => assert!(e.values_md["UNKNOWN"].is_deprecated()),
+            Type::Message(_) => panic!("Status should be an enum"),
+        }
+    }
+
+    #[test]
+    fn it_should_reject_invalid_field_numbers() {
+        let file_path: PathBuf = "test.proto".into();
+
+        for id in [19000, 0, 536870912] {
+            let text = format!("message Foo {{ int32 foo = {}; }}", id);
+            let parser = FileParser::new(file_path.clone(), text.chars());
+            let error = parser.parse().expect_err("field number should be rejected");
+            assert_eq!(error.0, ParseError::ReservedFieldNumber(id));
+        }
+    }
+
+    #[test]
+    fn it_should_relax_missing_semi_after_last_enum_value() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        enum Status {
+            UNKNOWN = 0;
+            STARTED = 1 [deprecated = true]
+        }
+        enum Other { NONE = 0 }
+        "#;
+
+        let parser = FileParser::new(file_path.clone(), text.chars());
+        let ns = parser.parse()?;
+        let values = ns.types.get("Status").map(|t| match t {
+            Type::Enum(e) => (e.values.len(), e.values_md["STARTED"].is_deprecated()),
+            Type::Message(_) => (0, false),
+        });
+        assert_eq!(values, Some((2, true)));
+
+        let mut parser = FileParser::new(file_path, text.chars());
+        parser.set_strict(true);
+        assert!(parser.parse().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_parse_tab_delimited_tokens() -> Result<(), Box<dyn std::error::Error>> {
+        let spaced = "package pb.hello;\nmessage Foo {\n  // leading\n  optional int32 foo = 1;\n  map<string, Foo> bar = 2 [deprecated = true];\n}\nenum Status { UNKNOWN = 0; }\nservice Hello { rpc Get(Foo) returns (Foo); }\n";
+        let tabbed = "package\tpb.hello;\nmessage\tFoo\t{\n\t//\tleading\n\toptional\tint32\tfoo\t=\t1;\n\tmap<string,\tFoo>\tbar\t=\t2\t[deprecated\t=\ttrue];\n}\nenum\tStatus\t{\tUNKNOWN\t=\t0;\t}\nservice\tHello\t{\trpc\tGet(Foo)\treturns\t(Foo);\t}\n";
+
+        let mut results = Vec::new();
+        for text in [spaced, tabbed] {
+            let file_path: PathBuf = "test.proto".into();
+            let ns = FileParser::new(file_path, text.chars()).parse()?;
+
+            let msg = ns
+                .types
+                .get("Foo")
+                .and_then(|t| t.as_message())
+                .expect("Foo should be parsed");
+
+            let comments = msg
+                .fields
+                .values()
+                .map(|f| {
+                    let text = f.md.comment.as_ref().map(|c| c.text.trim().to_string());
+                    (f.md.line, text, f.md.is_deprecated())
+                })
+                .collect::<Vec<_>>();
+
+            results.push((serde_json::to_string(&ns)?, ns.path, comments));
+        }
+
+        assert_eq!(results[0], results[1]);
+        assert_eq!(
+            results[0].2,
+            vec![(4, Some("leading".to_string()), false), (5, None, true)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_reject_rule_on_map_field() {
+        let file_path: PathBuf = "test.proto".into();
+        let text = "message Foo { repeated map<string, int32> foo = 1; }";
+
+        let parser = FileParser::new(file_path, text.chars());
+        let error = parser.parse().expect_err("rule on map field should fail");
+        assert_eq!(error.0, ParseError::RuleOnMapField(FieldRule::Repeated));
+    }
+
+    #[test]
+    fn it_should_parse_reserved() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        message Foo {
+            reserved 2, 15, 9 to 11, 40 to max;
+            reserved "foo", "bar";
+        }
+        "#;
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let reserved = ns
+            .types
+            .get("Foo")
+            .and_then(|t| t.as_message())
+            .map(|msg| &msg.reserved);
+
+        assert_eq!(
+            reserved,
+            Some(&vec![
+                Reserved::Range(2, 2),
+                Reserved::Range(15, 15),
+                Reserved::Range(9, 11),
+                Reserved::Range(40, 536870911),
+                Reserved::Name("foo".to_string()),
+                Reserved::Name("bar".to_string()),
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_parse_enum_reserved() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        enum Foo {
+            reserved 1 to 5, 9, 20 to max;
+            reserved "BAR";
+            FOO = 0;
+            BAZ = 6;
+        }
+        "#;
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let reserved = match ns.types.get("Foo") {
+            Some(Type::Enum(e)) => &e.reserved,
+            _ => panic!("Foo should be an enum"),
+        };
+
+        assert_eq!(
+            reserved,
+            &vec![
+                Reserved::Range(1, 5),
+                Reserved::Range(9, 9),
+                Reserved::Range(20, i32::MAX),
+                Reserved::Name("BAR".to_string()),
+            ]
+        );
+
+        for (value, name) in [("BAZ = 3", "BAZ"), ("BAR = 6", "BAR")] {
+            let text = format!(
+                "enum Foo {{ reserved 1 to 5; reserved \"BAR\"; {}; }}",
+                value
+            );
+            let error = FileParser::new(PathBuf::from("test.proto"), text.chars())
+                .parse()
+                .unwrap_err();
+
+            assert_eq!(
+                error.0,
+                ParseError::UseOfReservedEnumValue(name.to_string())
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_inline_map_entries() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        package pb;
+        message Foo {
+            message LabelsEntry {
+                option map_entry = true;
+                string key = 1;
+                Bar value = 2;
+            }
+            message Bar {}
+            message UnusedEntry {
+                option map_entry = true;
+                string key = 1;
+                string value = 2;
+            }
+            repeated LabelsEntry labels = 1;
+            repeated Foo.LabelsEntry qualified_labels = 2;
+            repeated .pb.Foo.LabelsEntry absolute_labels = 3;
+            repeated .other.Foo.LabelsEntry other_labels = 4;
+        }
+        "#;
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let msg = ns.types.get("Foo").and_then(|t| t.as_message()).unwrap();
+
+        // entries that are not referenced are kept
+        assert_eq!(
+            msg.nested.keys().collect::<Vec<_>>(),
+            vec!["Bar", "UnusedEntry"]
+        );
+        assert_eq!(
+            serde_json::to_value(&msg.fields["labels"])?,
+            serde_json::json!({ "type": "Bar", "id": 1, "keyType": "string" })
+        );
+
+        for name in ["qualified_labels", "absolute_labels"] {
+            assert_eq!(msg.fields[name].key_type.as_deref(), Some("string"));
+            assert_eq!(*msg.fields[name].type_name.borrow(), "Bar");
+        }
+
+        let other = &msg.fields["other_labels"];
+        assert_eq!(other.rule, Some(FieldRule::Repeated));
+        assert_eq!(*other.type_name.borrow(), ".other.Foo.LabelsEntry");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_report_missing_rpc_returns() {
+        let text = "service Greeter { rpc Foo(Req); }";
+        let error = FileParser::new(PathBuf::from("test.proto"), text.chars())
+            .parse()
+            .unwrap_err();
+
+        assert_eq!(error.0, ParseError::MissingRpcReturns("Foo".to_string()));
+        assert_eq!(
+            error.0.to_string(),
+            "rpc Foo is missing its returns (...) clause"
+        );
+    }
+
+    #[test]
+    fn it_should_collect_floating_comments() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        message Foo {
+            int32 a = 1;
+
+            // floating comment
+
+            // leading comment attached to b
+            int32 b = 2;
+
+            /* another floating comment */
+        }
+        "#;
+
+        let mut parser = FileParser::new(file_path, text.chars());
+        parser.set_collect_floating_comments(true);
+        let ns = parser.parse()?;
+        let msg = ns.types.get("Foo").and_then(|t| t.as_message()).unwrap();
+
+        assert_eq!(
+            msg.floating_comments
+                .iter()
+                .map(|cmt| cmt.text.as_str())
+                .collect::<Vec<_>>(),
+            vec![" floating comment", " another floating comment "]
+        );
+        assert_eq!(
+            msg.fields["b"].md.comment_text(),
+            Some(" leading comment attached to b")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_relax_semicolons_after_closing_braces() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        message Foo {
+            message Bar {
+                message Baz {};
+                int32 id = 1;
+            };
+            enum Status {
+                UNKNOWN = 0;
+            };
+            oneof choice {
+                string name = 2;
+            };
+            Bar bar = 3;
+        };
+
+        enum Kind {
+            DEFAULT = 0;
+        };
+
+        service Greeter {
+            rpc Greet (Foo) returns (Foo) {};
+        };
+        "#;
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let foo = ns.types.get("Foo").and_then(|t| t.as_message()).unwrap();
+        let bar = foo.nested.get("Bar").and_then(|t| t.as_message()).unwrap();
+
+        assert_eq!(foo.fields.keys().collect::<Vec<_>>(), vec!["name", "bar"]);
+        assert!(matches!(foo.nested.get("Status"), Some(Type::Enum(_))));
+        assert!(bar.nested.contains_key("Baz"));
+        assert!(matches!(ns.types.get("Kind"), Some(Type::Enum(_))));
+        assert!(ns.services["Greeter"].methods.contains_key("Greet"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_parse_gogo_and_ctype_options() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        message Foo {
+            string a = 1 [ctype = CORD];
+            bytes b = 2 [(gogoproto.customtype) = "github.com/foo/bar.Uuid", (gogoproto.nullable) = false];
+            Bar c = 3 [(gogoproto.embed) = true, (gogoproto.moretags) = 'yaml:"c,omitempty"'];
+            repeated int64 d = 4 [( gogoproto . casttype ) = "Id", packed = true];
+            map<string, Bar> e = 5 [(gogoproto.castkey) = "Key"];
+        }
+        "#;
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let msg = ns.types.get("Foo").and_then(|t| t.as_message()).unwrap();
+        let fields = msg
+            .fields
+            .iter()
+            .map(|(name, field)| {
+                (
+                    name.as_str(),
+                    field.id,
+                    field.type_name.borrow().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            fields,
+            vec![
+                ("a", 1, "string".to_string()),
+                ("b", 2, "bytes".to_string()),
+                ("c", 3, "Bar".to_string()),
+                ("d", 4, "int64".to_string()),
+                ("e", 5, "Bar".to_string()),
+            ]
+        );
+
+        assert_eq!(msg.fields["a"].md.option_value("ctype"), Some("CORD"));
+        assert_eq!(
+            msg.fields["b"].md.option_value("gogoproto.customtype"),
+            Some("github.com/foo/bar.Uuid")
+        );
+        assert_eq!(
+            msg.fields["b"].md.option_value("gogoproto.nullable"),
+            Some("false")
+        );
+        assert_eq!(
+            msg.fields["c"].md.option_value("gogoproto.moretags"),
+            Some("yaml:\"c,omitempty\"")
+        );
+        assert_eq!(
+            msg.fields["d"].md.option_value("gogoproto.casttype"),
+            Some("Id")
+        );
+        assert_eq!(
+            msg.fields["e"].md.option_value("gogoproto.castkey"),
+            Some("Key")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_parse_rpc_with_empty_body() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text =
+            "service Greeter { rpc Foo(Req) returns (Res) {} rpc Bar(Req) returns (Res) {} }";
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let methods = &ns.services["Greeter"].methods;
+
+        assert_eq!(methods.keys().collect::<Vec<_>>(), vec!["Foo", "Bar"]);
+        assert!(methods["Foo"].md.options.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_report_unexpected_rpc_statement() {
+        let text = "service Greeter { rpc Foo(Req) returns (Res) { string name = 1; } }";
+        let error = FileParser::new(PathBuf::from("test.proto"), text.chars())
+            .parse()
+            .unwrap_err();
+
+        assert_eq!(
+            error.0,
+            ParseError::UnexpectedRpcStatement {
+                method: "Foo".to_string(),
+                found: Token::Identifier("string".to_string()),
+            }
+        );
+        assert_eq!(
+            error.0.to_string(),
+            "unexpected statement \"string\" in rpc Foo, only options are allowed"
+        );
+    }
+
+    #[test]
+    fn it_should_forbid_required_fields() {
+        let text = indoc! {r#"
+        syntax = "proto2";
+        message Foo {
+          required string name = 1;
+          optional int32 age = 2;
+        }
+        "#};
+
+        let ns = FileParser::new(PathBuf::from("test.proto"), text.chars())
+            .parse()
+            .expect("required fields are allowed by default");
+        let msg = ns.types["Foo"].as_message().unwrap();
+        assert!(matches!(msg.fields["name"].rule, Some(FieldRule::Required)));
+
+        let mut parser = FileParser::new(PathBuf::from("test.proto"), text.chars());
+        parser.set_forbid_required(true);
+        let error = parser
+            .parse()
+            .expect_err("required fields should be rejected");
+        assert_eq!(
+            error.0,
+            ParseError::RequiredFieldForbidden {
+                field: "name".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn it_should_prefix_nested_option_keys() -> Result<(), Box<dyn std::error::Error>> {
+        let text = indoc! {r#"
+        message Foo {
+          option (foo.bar) = { a: 1, nested { b: "x" c: { d: true } } e: 2 };
+          option (foo.baz).a = { b: 1 };
+        }
+        "#};
+
+        let ns = FileParser::new(PathBuf::from("test.proto"), text.chars()).parse()?;
+        let msg = ns.types["Foo"].as_message().unwrap();
+
+        assert_eq!(
+            msg.md.options,
+            vec![
+                vec![
+                    "foo.bar",
+                    "a",
+                    "1",
+                    "nested.b",
+                    "x",
+                    "nested.c.d",
+                    "true",
+                    "e",
+                    "2"
+                ],
+                vec!["foo.baz", "a.b", "1"],
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_require_a_zero_value_in_proto3_enums() {
+        let valid = indoc! {r#"
+        syntax = "proto3";
+        enum Status {
+          UNKNOWN = 0;
+          STARTED = 1;
+        }
+        "#};
+
+        let ns = FileParser::new(PathBuf::from("test.proto"), valid.chars())
+            .parse()
+            .expect("it should parse an enum starting with a zero value");
+        assert!(ns.types.contains_key("Status"));
+
+        for values in ["STARTED = 1; UNKNOWN = 0;", "STARTED = 1;", ""] {
+            let text = format!("syntax = \"proto3\"; enum Status {{ {} }}", values);
+            let error = FileParser::new(PathBuf::from("test.proto"), text.chars())
+                .parse()
+                .expect_err("it should reject an enum without a leading zero value");
+            assert_eq!(
+                error.0,
+                ParseError::Proto3EnumMissingZero("Status".to_string())
+            );
+        }
+
+        // proto2 enums can start with any value
+        let text = "syntax = \"proto2\"; enum Status { STARTED = 1; }";
+        assert!(FileParser::new(PathBuf::from("test.proto"), text.chars())
+            .parse()
+            .is_ok());
+    }
+
+    #[test]
+    fn it_should_ignore_inline_comments_in_field_declarations(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let text = indoc! {r#"
+        message Foo {
+          // the foo field
+          int32 foo = /* id */ 1;
+          // the bar field
+          int32 bar /* name */ = 2;
+          int32 baz = /* id */ 3;
+        }
+        "#};
+
+        let ns = FileParser::new(PathBuf::from("test.proto"), text.chars()).parse()?;
+        let msg = ns.types["Foo"].as_message().unwrap();
+
+        let fields = msg
+            .fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field.id, field.md.comment_text()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            fields,
+            vec![
+                ("foo", 1, Some(" the foo field")),
+                ("bar", 2, Some(" the bar field")),
+                ("baz", 3, None),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_attach_enum_value_comments() -> Result<(), Box<dyn std::error::Error>> {
+        let text = indoc! {r#"
+        enum Status {
+          UNKNOWN = 0; // unknown status
+          STARTED = 1; // the job started
+          // the job is running
+          RUNNING = 2;
+          DONE = 3 [deprecated = true]; // the job is done
+          FAILED = 4;
+        }
+        "#};
+
+        let ns = FileParser::new(PathBuf::from("test.proto"), text.chars()).parse()?;
+        let e = match &ns.types["Status"] {
+            Type::Enum(e) => e,
+            Type::Message(_) => panic!("Status should be an enum"),
+        };
+
+        let comments = e
+            .values
+            .keys()
+            .map(|name| (name.as_str(), e.value_comment(name)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            comments,
+            vec![
+                ("UNKNOWN", Some(" unknown status")),
+                ("STARTED", Some(" the job started")),
+                ("RUNNING", Some(" the job is running")),
+                ("DONE", Some(" the job is done")),
+                ("FAILED", None),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_read_message_option_values() -> Result<(), Box<dyn std::error::Error>> {
+        let text = indoc! {r#"
+        message Book {
+          option (google.api.resource) = {
+            type: "library.googleapis.com/Book"
+            pattern: "shelves/{shelf}/books/{book}"
+            history: { style: ORIGINALLY_SINGLE_PATTERN }
+          };
+          option (lyft.table).name = "books";
+          string name = 1;
+        }
+        "#};
+
+        let ns = FileParser::new(PathBuf::from("test.proto"), text.chars()).parse()?;
+        let md = &ns.types["Book"].as_message().unwrap().md;
+
+        let resource = |path| md.option_path_value("google.api.resource", path);
+        assert_eq!(resource("type"), Some("library.googleapis.com/Book"));
+        assert_eq!(resource("pattern"), Some("shelves/{shelf}/books/{book}"));
+        assert_eq!(resource("history.style"), Some("ORIGINALLY_SINGLE_PATTERN"));
+        assert_eq!(resource("name"), None);
+
+        assert_eq!(md.option_path_value("lyft.table", "name"), Some("books"));
+        assert_eq!(md.option_path_value("lyft.table", "type"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_record_oneof_membership() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        message Foo {
+            int32 foo = 1;
+            oneof choice {
+                string bar = 2;
+                int32 baz = 3;
+            }
+        }
+        "#;
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let message = ns
+            .types
+            .get("Foo")
+            .and_then(|t| t.as_message())
+            .expect("Foo should be defined");
+
+        let oneof_names = message
+            .fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field.oneof_name.as_deref()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            oneof_names,
+            vec![
+                ("foo", None),
+                ("bar", Some("choice")),
+                ("baz", Some("choice"))
+            ]
+        );
+
+        assert_eq!(
+            serde_json::to_string(&message.fields["bar"])?,
+            r#"{"type":"string","id":2,"oneofName":"choice"}"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_reject_json_name_collisions() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+
+        for (text, json_name) in [
+            (
+                r#"syntax = "proto3"; message Foo { int32 foo_bar = 1; int32 fooBar = 2; }"#,
+                "fooBar",
+            ),
+            (
+                r#"syntax = "proto3"; message Foo { int32 foo = 1; int32 bar = 2 [json_name = "foo"]; }"#,
+                "foo",
+            ),
+        ] {
+            let parser = FileParser::new(file_path.clone(), text.chars());
+            let error = parser.parse().expect_err("json name collision should fail");
+            assert!(
+                matches!(error.0, ParseError::JsonNameCollision { json_name: ref name, .. } if name == json_name),
+                "{:?}",
+                error
+            );
+        }
+
+        // proto2 files are not checked
+        let text = "message Foo { int32 foo_bar = 1; int32 fooBar = 2; }";
+        FileParser::new(file_path, text.chars()).parse()?;
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_reject_use_of_reserved_field() {
+        let file_path: PathBuf = "test.proto".into();
+
+        for text in [
+            "message Foo { reserved 9 to 11; int32 foo = 10; }",
+            "message Foo { int32 foo = 1; reserved \"foo\"; }",
+        ] {
+            let parser = FileParser::new(file_path.clone(), text.chars());
+            let error = parser.parse().expect_err("reserved field should fail");
+            assert_eq!(error.0, ParseError::UseOfReservedField("foo".to_string()));
+        }
+    }
+
+    #[test]
+    fn it_should_default_to_proto2_syntax() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+
+        for (text, expected) in [
+            ("message Foo {}", Syntax::Proto2),
+            ("syntax = \"proto2\"; message Foo {}", Syntax::Proto2),
+            ("syntax = \"proto3\"; message Foo {}", Syntax::Proto3),
+            ("syntax='proto3'; message Foo {}", Syntax::Proto3),
+        ] {
+            let ns = FileParser::new(file_path.clone(), text.chars()).parse()?;
+            assert_eq!(ns.syntax, expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_reject_unquoted_syntax() {
+        let file_path: PathBuf = "test.proto".into();
+        let parser = FileParser::new(file_path, "syntax = proto3;".chars());
+        let error = parser.parse().expect_err("unquoted syntax should fail");
+        assert_eq!(error.0, ParseError::UnquotedSyntax("proto3".to_string()));
+    }
+
+    #[test]
+    fn it_should_ignore_empty_options() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        option;
+        message Foo {
+            option;
+            option deprecated;
+            int32 foo = 1;
+            int32 bar = 2 [deprecated = true];
+        }
+        "#;
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let message = ns
+            .types
+            .get("Foo")
+            .and_then(|t| t.as_message())
+            .expect("Foo should be defined");
+
+        assert_eq!(message.md.options, vec![vec!["deprecated".to_string()]]);
+        assert!(message.fields["foo"].md.options.is_empty());
+        assert!(message.fields["bar"].md.is_deprecated());
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_parse_idempotency_level() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path: PathBuf = "test.proto".into();
+        let text = r#"
+        service Foo {
+            rpc Get(A) returns (B) {
+                option idempotency_level = NO_SIDE_EFFECTS;
+            }
+            rpc Put(A) returns (B) {
+                option idempotency_level = IDEMPOTENT;
+            }
+            rpc Post(A) returns (B);
+        }
+        "#;
+
+        let ns = FileParser::new(file_path, text.chars()).parse()?;
+        let levels = ns.services["Foo"]
+            .methods
+            .iter()
+            .map(|(name, rpc)| (name.as_str(), rpc.idempotency_level))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            levels,
+            vec![
+                ("Get", IdempotencyLevel::NoSideEffects),
+                ("Put", IdempotencyLevel::Idempotent),
+                ("Post", IdempotencyLevel::Unknown),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_reject_duplicate_services_and_rpcs() {
+        let file_path: PathBuf = "test.proto".into();
+
+        for (text, expected) in [
+            (
+                "service Foo { rpc Bar(A) returns (B); rpc Bar(C) returns (D); }",
+                ParseError::DuplicateRpc {
+                    service: "Foo".to_string(),
+                    method: "Bar".to_string(),
+                },
+            ),
+            (
+                "service Foo {} service Foo {}",
+                ParseError::DuplicateService("Foo".to_string()),
+            ),
+        ] {
+            let parser = FileParser::new(file_path.clone(), text.chars());
+            let error = parser.parse().expect_err("duplicate should be rejected");
+            assert_eq!(error.0, expected);
+        }
+    }
+
     #[test]
     fn playground() -> Result<(), Box<dyn std::error::Error>> {
         let file_path: PathBuf = "test.proto".into();
@@ -608,4 +1862,17 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_enum_value_out_of_range() {
+        let text = "enum Foo { A = 0; B = 0xFFFFFFFF; }";
+        let error = FileParser::new(PathBuf::from("test.proto"), text.chars())
+            .parse()
+            .unwrap_err();
+
+        assert_eq!(
+            error.0,
+            ParseError::EnumValueOutOfRange("0xFFFFFFFF".to_string())
+        );
+    }
 }
@@ -2,7 +2,7 @@ use std::borrow::Cow;
 
 use crate::metadata::ProtoOption;
 use lazy_static::lazy_static;
-use regex::Regex;
+use regex::{Captures, Regex};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct HTTPErrorType<'a> {
@@ -16,102 +16,177 @@ impl<'a> HTTPErrorType<'a> {
     }
 }
 
+/// An HTTP method and path an rpc is bound to
 #[derive(Debug, PartialEq, Eq)]
-pub struct HTTPOptions<'a> {
+pub struct HTTPBinding<'a> {
+    /// The normalized path, see [normalize_path]
     pub path: Cow<'a, str>,
+
+    /// The path as defined in the proto file (e.g `/hello/<string:name>`)
+    pub original_path: &'a str,
+
     pub method: &'a str,
+}
+
+/// HTTP methods that can be used as keys of a `pgm.http.rule` option
+const HTTP_METHODS: [&str; 7] = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct HTTPOptions<'a> {
+    /// The HTTP bindings, a rule can define multiple methods (e.g `{ GET: "/a", POST: "/a" }`)
+    pub bindings: Vec<HTTPBinding<'a>>,
     pub error_types: Vec<HTTPErrorType<'a>>,
 }
 
 impl<'a> HTTPOptions<'a> {
+    /// Returns the (code, type) tuples of the errors returned by the rpc.
+    /// The default error type uses the "number" code
+    pub fn error_map(&self) -> Vec<(String, String)> {
+        self.error_types
+            .iter()
+            .map(|e| (e.code.to_string(), e.type_name.to_string()))
+            .collect()
+    }
+
     pub fn from(raw_options: &'a [ProtoOption]) -> Option<Self> {
+        let mut rules = Vec::new();
         let mut path = None;
         let mut method = None;
         let mut error_types = Vec::new();
         let mut default_error = None;
 
         for option in raw_options {
-            let option = option.iter().map(String::as_str).collect::<Vec<_>>();
+            let (name, entries) = match option_entries(option) {
+                Some(option) => option,
+                None => continue,
+            };
 
-            match option[..] {
-                ["pgm.http.rule", rule_method, rule_path] => {
-                    path.replace(rule_path);
-                    method.replace(rule_method);
+            // the keys of the default error type and of the error overrides differ between dialects
+            let (default_error_key, error_override_key) = match name {
+                "pgm.http.rule" => {
+                    rules = entries
+                        .iter()
+                        .filter_map(|(key, path)| {
+                            HTTP_METHODS
+                                .iter()
+                                .find(|m| m.eq_ignore_ascii_case(key))
+                                .map(|method| (*method, *path))
+                        })
+                        .collect();
+                    continue;
                 }
-                ["pgm.error.rule", "default_error_type", type_name, ..] => {
-                    default_error.replace(HTTPErrorType {
-                        code: "number",
-                        type_name,
-                    });
-
-                    for error_override in option[3..].chunks(5) {
-                        match error_override {
-                            ["error_override", "type", type_name, "code", code]
-                            | ["error_override", "code", code, "type", type_name] => {
-                                error_types.push(HTTPErrorType { code, type_name });
-                            }
-                            _ => {}
-                        }
+                "pgm.error.rule" => ("default_error_type", "error_override"),
+                "http.http_options" => ("error_type", "error_overrides"),
+                _ => continue,
+            };
+
+            // the (code, type) of the error override being read
+            let mut error_override = (None, None);
+
+            for (key, value) in entries {
+                match key.split_once('.') {
+                    Some((prefix, "code")) if prefix == error_override_key => {
+                        error_override.0 = Some(value);
                     }
+                    Some((prefix, "type")) if prefix == error_override_key => {
+                        error_override.1 = Some(value);
+                    }
+                    _ if key == default_error_key => {
+                        default_error.replace(HTTPErrorType {
+                            code: "number",
+                            type_name: value,
+                        });
+                    }
+                    _ if name == "http.http_options" && key == "path" => {
+                        path.replace(value);
+                    }
+                    _ if name == "http.http_options" && key == "method" => {
+                        method.replace(value);
+                    }
+                    _ => {}
                 }
-                ["http.http_options", ".path", v] => {
-                    path.replace(v);
-                }
-                ["http.http_options", ".method", v] => {
-                    method.replace(v);
-                }
-                ["http.http_options", ".error_type", type_name] => {
-                    default_error.replace(HTTPErrorType {
-                        code: "number",
-                        type_name,
-                    });
-                }
-                ["http.http_options", ".error_overrides", "code", code, "type", type_name]
-                | ["http.http_options", ".error_overrides", "type", type_name, "code", code] => {
+
+                if let (Some(code), Some(type_name)) = error_override {
                     error_types.push(HTTPErrorType { code, type_name });
+                    error_override = (None, None);
                 }
+            }
+        }
 
-                _ => {}
+        if rules.is_empty() {
+            if let (Some(path), Some(method)) = (path, method) {
+                rules.push((method, path));
             }
         }
 
-        match (path, method) {
-            (Some(path), Some(method)) => {
-                if let Some(default_error) = default_error {
-                    error_types.push(default_error)
-                }
+        if rules.is_empty() {
+            return None;
+        }
 
-                if error_types.is_empty() {
-                    error_types.push(HTTPErrorType {
-                        code: "number",
-                        type_name: "unknown",
-                    })
-                }
+        if let Some(default_error) = default_error {
+            error_types.push(default_error)
+        }
 
-                lazy_static! {
-                    // replace /api/<foo:string> => /api/:foo
-                    static ref HTTP_REGEX: Regex = Regex::new("(<.*?:(.*?)>)").unwrap();
-                }
+        if error_types.is_empty() {
+            error_types.push(HTTPErrorType {
+                code: "number",
+                type_name: "unknown",
+            })
+        }
 
-                // let path = HTTP_REGEX.replace_all(path, ":$2");
-                let path = HTTP_REGEX.replace_all(path, ":$2");
+        let bindings = rules
+            .into_iter()
+            .map(|(method, path)| HTTPBinding {
+                path: normalize_path(path),
+                original_path: path,
+                method,
+            })
+            .collect();
 
-                Some(HTTPOptions {
-                    path,
-                    method,
-                    error_types,
-                })
-            }
-            _ => None,
-        }
+        Some(HTTPOptions {
+            bindings,
+            error_types,
+        })
+    }
+}
+
+/// Returns the name and the (key, value) entries of an option, nested keys are joined with a "."
+/// (e.g `error_override.code`). The single value and block forms are read the same way,
+/// e.g `(foo).bar = 1` and `(foo) = { bar: 1 }` both return `("foo", [("bar", "1")])`
+fn option_entries(option: &[String]) -> Option<(&str, Vec<(&str, &str)>)> {
+    let (name, values) = option.split_first()?;
+    let entries = values
+        .chunks_exact(2)
+        .map(|kv| (kv[0].as_str(), kv[1].as_str()))
+        .collect();
+
+    Some((name.as_str(), entries))
+}
+
+/// Normalize the path parameters of an HTTP path template into the `:name` form
+///
+/// - `/api/<string:foo>` => `/api/:foo`
+/// - `/api/<foo>` => `/api/:foo`
+/// - `/api/{foo}` => `/api/:foo`
+/// - `/api/{foo=bar/*}` => `/api/:foo`
+/// - `/api/:foo` is kept as-is
+pub fn normalize_path(path: &str) -> Cow<'_, str> {
+    lazy_static! {
+        static ref HTTP_REGEX: Regex =
+            Regex::new(r"<(?:[^:>]*:)?([^>]+)>|\{([^}=]+)(?:=[^}]*)?\}").unwrap();
     }
+
+    HTTP_REGEX.replace_all(path, |caps: &Captures| {
+        let name = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+        format!(":{}", name.trim())
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         file_parser::FileParser,
-        http_options::{HTTPErrorType, HTTPOptions},
+        http_options::{normalize_path, HTTPBinding, HTTPErrorType, HTTPOptions},
         metadata::ProtoOption,
     };
     use indoc::indoc;
@@ -159,8 +234,11 @@ mod tests {
         }
         "#},
         HTTPOptions {
-            method: "GET",
-            path: "/hello".into(),
+            bindings: vec![HTTPBinding {
+                method: "GET",
+                path: "/hello".into(),
+                original_path: "/hello",
+            }],
             error_types: vec![
                 HTTPErrorType {
                     code: "404",
@@ -191,8 +269,11 @@ mod tests {
         }
         "#},
         HTTPOptions {
-            method: "GET",
-            path: "/hello".into(),
+            bindings: vec![HTTPBinding {
+                method: "GET",
+                path: "/hello".into(),
+                original_path: "/hello",
+            }],
             error_types: vec![
                 HTTPErrorType {
                     code: "404",
@@ -206,6 +287,29 @@ mod tests {
         }
     );
 
+    test_http_options!(
+        test_extension_name_with_spaces,
+        indoc! {r#"
+        service HelloWorld {
+          rpc GetHello (SayHelloRequest) returns (SayHelloResponse) {
+              option ( pgm.http.rule ) = { GET: "/hello" };
+              option ( .pgm . error.rule ) = { default_error_type: "DefaultError" };
+          }
+        }
+        "#},
+        HTTPOptions {
+            bindings: vec![HTTPBinding {
+                method: "GET",
+                path: "/hello".into(),
+                original_path: "/hello",
+            }],
+            error_types: vec![HTTPErrorType {
+                code: "number",
+                type_name: "DefaultError",
+            },]
+        }
+    );
+
     test_http_options!(
         test_dynamic_path,
         indoc! {r#"
@@ -216,8 +320,11 @@ mod tests {
         }
         "#},
         HTTPOptions {
-            method: "GET",
-            path: "/hello/:one/:two".into(),
+            bindings: vec![HTTPBinding {
+                method: "GET",
+                path: "/hello/:one/:two".into(),
+                original_path: "/hello/<string:one>/<string:two>",
+            }],
             error_types: vec![HTTPErrorType {
                 code: "number",
                 type_name: "unknown",
@@ -225,6 +332,176 @@ mod tests {
         }
     );
 
+    test_http_options!(
+        test_multiple_verbs,
+        indoc! {r#"
+        service HelloWorld {
+          rpc GetHello (SayHelloRequest) returns (SayHelloResponse) {
+              option (pgm.http.rule) = { GET: "/hello/<string:name>", POST: "/hello" };
+          }
+        }
+        "#},
+        HTTPOptions {
+            bindings: vec![
+                HTTPBinding {
+                    method: "GET",
+                    path: "/hello/:name".into(),
+                    original_path: "/hello/<string:name>",
+                },
+                HTTPBinding {
+                    method: "POST",
+                    path: "/hello".into(),
+                    original_path: "/hello",
+                },
+            ],
+            error_types: vec![HTTPErrorType {
+                code: "number",
+                type_name: "unknown",
+            },]
+        }
+    );
+
+    test_http_options!(
+        test_quoted_path_with_slashes_and_params,
+        indoc! {r#"
+        service HelloWorld {
+          rpc GetHello (SayHelloRequest) returns (SayHelloResponse) {
+              option (pgm.http.rule) = { GET: "/v1/users/{id}/posts/{post_id=*}" };
+          }
+        }
+        "#},
+        HTTPOptions {
+            bindings: vec![HTTPBinding {
+                method: "GET",
+                path: "/v1/users/:id/posts/:post_id".into(),
+                original_path: "/v1/users/{id}/posts/{post_id=*}",
+            }],
+            error_types: vec![HTTPErrorType {
+                code: "number",
+                type_name: "unknown",
+            },]
+        }
+    );
+
+    #[test]
+    fn test_error_map() {
+        for text in [
+            indoc! {r#"
+            service HelloWorld {
+              rpc GetHello (SayHelloRequest) returns (SayHelloResponse) {
+                option (http.http_options).path = "/hello";
+                option (http.http_options).method = "GET";
+                option (http.http_options).error_type = "DefaultError";
+                option (http.http_options).error_overrides = {code: 404, type: "404Error"};
+              }
+            }
+            "#},
+            indoc! {r#"
+            service HelloWorld {
+              rpc GetHello (SayHelloRequest) returns (SayHelloResponse) {
+                option (pgm.http.rule) = { GET: "/hello" };
+                option (pgm.error.rule) = {
+                  default_error_type: "DefaultError",
+                  error_override { code: 404, type: "404Error" }
+                };
+              }
+            }
+            "#},
+        ] {
+            let options = get_options(text);
+            let http_options = HTTPOptions::from(&options).expect("failed to parse HTTPOptions");
+
+            assert_eq!(
+                http_options.error_map(),
+                vec![
+                    ("404".to_string(), "404Error".to_string()),
+                    ("number".to_string(), "DefaultError".to_string()),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_option_dialects() {
+        let expected = HTTPOptions {
+            bindings: vec![HTTPBinding {
+                method: "GET",
+                path: "/hello".into(),
+                original_path: "/hello",
+            }],
+            error_types: vec![
+                HTTPErrorType {
+                    code: "404",
+                    type_name: "404Error",
+                },
+                HTTPErrorType {
+                    code: "500",
+                    type_name: "500Error",
+                },
+                HTTPErrorType {
+                    code: "number",
+                    type_name: "DefaultError",
+                },
+            ],
+        };
+
+        for text in [
+            indoc! {r#"
+            service HelloWorld {
+              rpc GetHello (SayHelloRequest) returns (SayHelloResponse) {
+                option (http.http_options) = {
+                  path: "/hello",
+                  method: "GET",
+                  error_type: "DefaultError",
+                  error_overrides { code: 404, type: "404Error" }
+                  error_overrides: { type: "500Error", code: 500 }
+                };
+              }
+            }
+            "#},
+            indoc! {r#"
+            service HelloWorld {
+              rpc GetHello (SayHelloRequest) returns (SayHelloResponse) {
+                option (http.http_options).path = "/hello";
+                option (http.http_options).method = "GET";
+                option (http.http_options).error_type = "DefaultError";
+                option (http.http_options).error_overrides = {code: 404, type: "404Error"};
+                option (http.http_options).error_overrides = {type: "500Error" code: 500};
+              }
+            }
+            "#},
+            indoc! {r#"
+            service HelloWorld {
+              rpc GetHello (SayHelloRequest) returns (SayHelloResponse) {
+                option (pgm.http.rule).get = "/hello";
+                option (pgm.error.rule) = {
+                  default_error_type: "DefaultError"
+                  error_override { code 404 type "404Error" }
+                  error_override: { type: "500Error", code: 500 }
+                };
+              }
+            }
+            "#},
+        ] {
+            let options = get_options(text);
+            let http_options = HTTPOptions::from(&options).expect("failed to parse HTTPOptions");
+            assert_eq!(http_options, expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        for path in [
+            "/hello/<string:one>/<string:two>",
+            "/hello/<one>/<two>",
+            "/hello/{one}/{two}",
+            "/hello/{one=users/*}/{two}",
+            "/hello/:one/:two",
+        ] {
+            assert_eq!(normalize_path(path), "/hello/:one/:two", "{}", path);
+        }
+    }
+
     #[test]
     fn test_no_http_options() {
         let options = get_options(indoc! {r#"
@@ -1,17 +1,25 @@
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
-/// Import represents a proto [import statement]
+/// Import represents a proto [import statement], along with the line where it's declared
 /// [import statement]: https://developers.google.com/protocol-buffers/docs/proto#importing_definitions
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Import {
-    Public(PathBuf),
-    Internal(PathBuf),
+    Public(PathBuf, usize),
+    Internal(PathBuf, usize),
 }
 
 impl Import {
     pub fn as_path(&self) -> &Path {
         match self {
-            Self::Public(v) | Self::Internal(v) => v.as_path(),
+            Self::Public(v, _) | Self::Internal(v, _) => v.as_path(),
+        }
+    }
+
+    /// Returns the line of the import statement
+    pub fn line(&self) -> usize {
+        match self {
+            Self::Public(_, line) | Self::Internal(_, line) => *line,
         }
     }
 }
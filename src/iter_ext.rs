@@ -1,21 +1,3 @@
-/// An Iterator wrapper that end with a last value
-#[derive(Clone)]
-pub struct EndWithIterator<I: Iterator> {
-    iter: I,
-    last: Option<I::Item>,
-}
-
-impl<I> Iterator for EndWithIterator<I>
-where
-    I: Iterator,
-{
-    type Item = I::Item;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().or_else(|| self.last.take())
-    }
-}
-
 /// An Iterator wrapper that start with an initial value
 #[derive(Clone)]
 pub struct StartWithIterator<I: Iterator> {
@@ -49,18 +31,6 @@ pub trait IterExt: Iterator {
         }
     }
 
-    /// End the iterator with the specified value
-    fn end_with<T>(self, last: T) -> EndWithIterator<Self>
-    where
-        Self: Sized,
-        T: Into<Self::Item>,
-    {
-        EndWithIterator {
-            iter: self,
-            last: Some(last.into()),
-        }
-    }
-
     /// compute the path relative to
     fn relative_to<'a, 'b, T>(mut self, mut dest: T) -> Self
     where
@@ -103,12 +73,6 @@ mod tests {
         assert_eq!(iter.collect::<Vec<_>>(), vec!["1", "2", "3", "4"]);
     }
 
-    #[test]
-    fn test_end_with() {
-        let iter = vec!["1", "2", "3"].into_iter().end_with("4");
-        assert_eq!(iter.collect::<Vec<_>>(), vec!["1", "2", "3", "4"]);
-    }
-
     fn test_relative_path(obj: &str, from: &str, expected: &str) {
         let result = obj
             .split('.')
@@ -63,12 +63,9 @@ impl<I: Iterator<Item = char>> Iterator for IteratorWithPosition<I> {
             return v;
         }
 
-        self.iter.next().map(|c| {
-            match c {
-                '\n' => self.position.add_line(),
-                _ => self.position.add_column(),
-            }
-            c
+        self.iter.next().inspect(|c| match c {
+            '\n' => self.position.add_line(),
+            _ => self.position.add_column(),
         })
     }
 }
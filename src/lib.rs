@@ -26,6 +26,12 @@
 //! Typescript definition are used to provide type hint and type checking.
 //! See [crate::typescript] for more details
 //! 
+//! ## std
+//! 
+//! The crate requires `std` and has no `no_std` build. Besides the filesystem [Parser](crate::parser::Parser),
+//! the parsed model identifies files with `Rc<Path>`, and relies on dependencies without an `alloc`-only mode
+//! (`thiserror`, `linked-hash-map`, `regex`).
+//! 
 //! 
 //! [FileDescriptorSet]: https://github.com/protocolbuffers/protobuf/blob/master/src/google/protobuf/descriptor.proto#L57 
 //! [protobuf.js]: https://github.com/protobufjs/protobuf.js
@@ -33,6 +39,7 @@
 
 extern crate lazy_static;
 
+mod cache;
 mod comment;
 mod r#enum;
 mod field;
@@ -42,6 +49,7 @@ mod import;
 mod into_path;
 mod iter_ext;
 mod iterator_with_position;
+pub mod lint;
 mod message;
 mod metadata;
 pub mod namespace;
@@ -49,10 +57,25 @@ mod oneof;
 mod parse_error;
 pub mod parser;
 mod position;
-mod scalar;
+mod reserved;
+pub mod scalar;
 mod service;
+mod syntax;
 pub mod service_map;
 mod token;
 mod tokenizer;
+mod validate;
 mod r#type;
 pub mod typescript;
+mod well_known_types;
+
+pub use comment::CommentKind;
+pub use field::JsType;
+pub use parse_error::{MergeError, TokenError};
+pub use position::Position;
+pub use scalar::is_scalar;
+pub use service::IdempotencyLevel;
+pub use syntax::Syntax;
+pub use token::Token;
+pub use tokenizer::{tokenize, Tokenizer};
+pub use validate::FieldConstraints;
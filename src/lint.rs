@@ -0,0 +1,168 @@
+//! Lint the parsed files and report the issues found as a [LintReport]
+//!
+//! See [crate::parser::Parser::lint] for more details
+
+use crate::{message::Message, r#type::Type};
+use derive_more::Display;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// The category of a lint diagnostic
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// Two fields of the same message use the same field number
+    #[display(fmt = "duplicate-field-id")]
+    DuplicateFieldId,
+
+    /// A field uses a reserved field number or name
+    #[display(fmt = "reserved-field")]
+    ReservedField,
+
+    /// A field type can't be resolved
+    #[display(fmt = "unresolved-type")]
+    UnresolvedType,
+
+    /// A field type is defined in a parsed file that is not imported
+    #[display(fmt = "missing-import")]
+    MissingImport,
+
+    /// An imported file is not used by any field or rpc
+    #[display(fmt = "unused-import")]
+    UnusedImport,
+
+    /// Two fields of the same message have the same JSON name
+    #[display(fmt = "json-name-collision")]
+    JsonNameCollision,
+}
+
+/// Configure which checks are run by [crate::parser::Parser::lint_with], all checks are enabled by default
+#[derive(Debug, Clone)]
+pub struct LintConfig {
+    pub duplicate_field_ids: bool,
+    pub reserved_fields: bool,
+    pub unresolved_types: bool,
+    pub missing_imports: bool,
+    pub unused_imports: bool,
+    pub json_name_collisions: bool,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            duplicate_field_ids: true,
+            reserved_fields: true,
+            unresolved_types: true,
+            missing_imports: true,
+            unused_imports: true,
+            json_name_collisions: true,
+        }
+    }
+}
+
+/// A lint issue found in a file
+#[derive(Display, Debug, PartialEq)]
+#[display(fmt = "{}:{} [{}] {}", "file.display()", line, kind, message)]
+pub struct Diagnostic {
+    /// The category of the issue
+    pub kind: LintKind,
+
+    /// The path of the file, relative to the root directory
+    pub file: PathBuf,
+
+    /// The line where the issue was found
+    pub line: usize,
+
+    /// A description of the issue
+    pub message: String,
+}
+
+/// The diagnostics found when linting the parsed files
+#[derive(Debug, Default)]
+pub struct LintReport {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LintReport {
+    /// Returns true if no issue was found
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Returns the diagnostics of the given kind
+    pub fn of_kind(&self, kind: LintKind) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(move |d| d.kind == kind)
+    }
+
+    /// Add a diagnostic to the report
+    pub fn add(&mut self, kind: LintKind, file: PathBuf, line: usize, message: String) {
+        self.diagnostics.push(Diagnostic {
+            kind,
+            file,
+            line,
+            message,
+        });
+    }
+}
+
+/// Run the message level checks on the given message and its nested messages
+pub fn lint_message(
+    name: &str,
+    msg: &Message,
+    config: &LintConfig,
+    file: &Path,
+    report: &mut LintReport,
+) {
+    if config.duplicate_field_ids {
+        let mut ids: HashMap<u32, &str> = HashMap::new();
+        for (field_name, field) in msg.fields.iter() {
+            match ids.get(&field.id) {
+                Some(other) => report.add(
+                    LintKind::DuplicateFieldId,
+                    file.to_path_buf(),
+                    field.md.line,
+                    format!(
+                        "fields {} and {} of {} use the same number {}",
+                        other, field_name, name, field.id
+                    ),
+                ),
+                None => {
+                    ids.insert(field.id, field_name);
+                }
+            }
+        }
+    }
+
+    if config.reserved_fields {
+        if let Some(field_name) = msg.find_reserved_field() {
+            report.add(
+                LintKind::ReservedField,
+                file.to_path_buf(),
+                msg.fields[field_name].md.line,
+                format!("field {} of {} is reserved", field_name, name),
+            );
+        }
+    }
+
+    if config.json_name_collisions {
+        if let Some((first, second, json_name)) = msg.find_json_name_collision() {
+            report.add(
+                LintKind::JsonNameCollision,
+                file.to_path_buf(),
+                msg.fields[second].md.line,
+                format!(
+                    "fields {} and {} of {} have the same JSON name {}",
+                    first, second, name, json_name
+                ),
+            );
+        }
+    }
+
+    for (nested_name, t) in msg.nested.iter() {
+        if let Type::Message(nested) = t {
+            let nested_name = format!("{}.{}", name, nested_name);
+            lint_message(&nested_name, nested, config, file, report);
+        }
+    }
+}
@@ -1,7 +1,12 @@
 use globwalk::GlobWalkerBuilder;
 use prosecco::service_map;
 use prosecco::typescript::serializer::{PrintConfig, Printer};
-use prosecco::{namespace::Namespace, parser::Parser};
+use prosecco::{
+    namespace::{self, Namespace},
+    parser::Parser,
+};
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::time::Instant;
@@ -20,14 +25,13 @@ fn get_files<'a, 'b>(
     root_dir: &'a Path,
     patterns: &'b [&'b str],
 ) -> impl Iterator<Item = Rc<Path>> + 'a {
-    GlobWalkerBuilder::from_patterns(&root_dir, patterns)
+    GlobWalkerBuilder::from_patterns(root_dir, patterns)
         .build()
         .unwrap()
-        .into_iter()
         .filter_map(move |entry| {
             let path = entry.ok();
             let path = path?.into_path();
-            let path = path.strip_prefix(&root_dir).ok()?;
+            let path = path.strip_prefix(root_dir).ok()?;
             Some(Rc::<Path>::from(path))
         })
 }
@@ -44,23 +48,23 @@ fn parse(root_dir: PathBuf, patterns: &[&str]) -> Result<Namespace, Box<dyn std:
     }
 
     println!(
-        "Parsed {} files in {:?}",
-        parser.parsed_files.len(),
+        "Parsed {} files ({} bytes) in {:?}",
+        parser.parsed_file_count(),
+        parser.total_bytes(),
         start.elapsed()
     );
 
     let root = parser.build_root()?;
 
-    let output = serde_json::to_string_pretty(&root).unwrap();
     let output_file = "/Users/pgherveou/.bbl/descriptors.json";
-    std::fs::write(output_file, output)?;
+    let mut writer = BufWriter::new(File::create(output_file)?);
+    namespace::write_descriptors(&root, &mut writer)?;
+    writer.flush()?;
     println!("wrote {}", output_file);
 
-    let config = PrintConfig {
-        root_url: "https://github.com/lyft/idl/blob/master/protos".into(),
-        print_bubble_client: true,
-        print_network_client: true,
-    };
+    let config = PrintConfig::builder()
+        .root_url("https://github.com/lyft/idl/blob/master/protos")
+        .build();
 
     let printer = Printer::new(&config);
     let output = printer.into_string(&root);
@@ -1,5 +1,6 @@
 use crate::{
-    field::Field,
+    comment::Comment,
+    field::{Field, FieldRule},
     into_path::ToPath,
     metadata::Metadata,
     namespace::Namespace,
@@ -7,10 +8,12 @@ use crate::{
     parse_error::ResolveError,
     r#enum::Enum,
     r#type::{Resolver, Type},
-    scalar::SCALARS,
+    reserved::Reserved,
+    scalar::is_scalar,
 };
 use linked_hash_map::LinkedHashMap;
 use serde::Serialize;
+use std::collections::HashMap;
 
 /// Message defines a proto [message]
 ///
@@ -22,26 +25,45 @@ pub struct Message {
     pub oneofs: LinkedHashMap<String, Oneof>,
 
     /// A map of name => fields
+    /// Fields are serialized with their proto source names, which is what protobuf.js expects
+    /// when loaded with the `keepCase: true` option. See also [FieldCase](crate::typescript::serializer::FieldCase)
     pub fields: LinkedHashMap<String, Field>,
 
+    /// The list of reserved field ranges and names
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reserved: Vec<Reserved>,
+
     /// A map of name => [nested] message or enum
     ///
     /// [nested]: https://developers.google.com/protocol-buffers/docs/proto3#nested
     #[serde(skip_serializing_if = "LinkedHashMap::is_empty")]
     pub nested: LinkedHashMap<String, Type>,
 
+    /// Comments inside the message that are not attached to any definition.
+    /// Only collected when enabled, see [FileParser::set_collect_floating_comments](crate::file_parser::FileParser::set_collect_floating_comments)
+    #[serde(skip_serializing)]
+    pub floating_comments: Vec<Comment>,
+
     /// metadata associated to the Enum
     #[serde(skip_serializing)]
     pub md: Metadata,
 }
 
+impl Default for Message {
+    fn default() -> Self {
+        Self::new(Metadata::default())
+    }
+}
+
 impl Message {
     // Returns a new Message with the provided metadata
     pub fn new(md: Metadata) -> Self {
         Self {
             fields: LinkedHashMap::new(),
             oneofs: LinkedHashMap::new(),
+            reserved: Vec::new(),
             nested: LinkedHashMap::new(),
+            floating_comments: Vec::new(),
             md,
         }
     }
@@ -81,84 +103,378 @@ impl Message {
         self.fields.insert(name, field);
     }
 
-    /// Resolve and update all the types referenced inside this message to their absolute path
-    /// We iterate through the fields and the nested messages
-    pub fn resolve_types(
-        &self,
-        dependencies: &[&Namespace],
-        resolve_path: Vec<(&str, &LinkedHashMap<String, Type>)>,
-    ) -> Result<(), ResolveError> {
-        'fields: for (field_name, field) in self.fields.iter() {
-            let mut type_name = field.type_name.borrow_mut();
+    /// Returns the fields ordered by field number, as compared by protoc based tools
+    pub fn fields_sorted_by_id(&self) -> Vec<(&String, &Field)> {
+        let mut fields = self.fields.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|(_, field)| field.id);
+        fields
+    }
 
-            // Skip scalars
-            if SCALARS.contains(type_name.as_str()) {
-                continue;
-            }
+    /// Returns true if this is a synthetic map entry message generated by protoc (`option map_entry = true;`)
+    pub fn is_map_entry(&self) -> bool {
+        self.md.option_value("map_entry") == Some("true")
+    }
 
-            // The field's path (e.g pb.example.one.One.OneInner)
-            let mut type_path = type_name.split('.');
+    /// Replace the synthetic map entry nested messages with map fields,
+    /// e.g `repeated LabelsEntry labels = 1;` becomes `map<string, string> labels = 1;`.
+    /// `fqn` is the fully qualified name of the message, used to match qualified references to the entries
+    /// (e.g `Foo.LabelsEntry` or `.pb.Foo.LabelsEntry`). Entries that are not referenced by any field are kept
+    pub fn inline_map_entries(&mut self, fqn: &str) {
+        let entries = self
+            .nested
+            .iter()
+            .filter_map(|(name, t)| {
+                let entry = t.as_message().filter(|msg| msg.is_map_entry())?;
+                let key = entry.fields.get("key")?.type_name.borrow().to_string();
+                let value = entry.fields.get("value")?.type_name.borrow().to_string();
+                Some((name.to_string(), key, value))
+            })
+            .collect::<Vec<_>>();
 
-            // Resolve absolute types starting with a "." by using the list of namespace dependencies
-            if type_name.starts_with('.') {
-                type_path.next(); // skip first
-                for ns in dependencies {
-                    if ns.resolve_path(type_path.clone()).is_some() {
-                        continue 'fields;
-                    }
+        for (name, key, value) in entries {
+            let entry_fqn = format!("{}.{}", fqn, name);
+            let mut inlined = false;
+
+            for (_, field) in self.fields.iter_mut() {
+                if field.rule == Some(FieldRule::Repeated)
+                    && refers_to(&field.type_name.borrow(), &entry_fqn)
+                {
+                    field.rule = None;
+                    field.key_type = Some(key.clone());
+                    field.type_name.replace(value.clone());
+                    inlined = true;
                 }
+            }
 
-                return Err(ResolveError::UnresolvedField {
-                    type_name: type_name.to_string(),
-                    field: field_name.to_string(),
-                });
+            if inlined {
+                self.nested.remove(&name);
             }
+        }
+    }
 
-            // Walk through the resolve path backward until we resolve the type
-            // e.g if the message is defined in One.OneInner, we first try to find it in OneInner, then One, ...
-            for (index, (_, types)) in resolve_path.iter().rev().enumerate() {
-                if types.contains_path(type_path.clone()) {
-                    *type_name = dependencies[0]
-                        .path
-                        .iter()
-                        .map(|v| v.as_str())
-                        .chain(
-                            resolve_path
-                                .iter()
-                                .take(resolve_path.len() - index)
-                                .map(|(s, _)| *s)
-                                .chain(type_path),
-                        )
-                        .collect::<Vec<_>>()
-                        .to_path_string();
-
-                    continue 'fields;
-                }
+    /// Add reserved field ranges or names
+    pub fn add_reserved(&mut self, reserved: Vec<Reserved>) {
+        self.reserved.extend(reserved);
+    }
+
+    /// Returns the name of the first field that uses a reserved number or name if any
+    pub fn find_reserved_field(&self) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(name, field)| {
+                self.reserved
+                    .iter()
+                    .any(|r| r.contains_name(name) || r.contains_id(field.id as i32))
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the names of the first two fields sharing the same JSON name, along with that name, if any
+    pub fn find_json_name_collision(&self) -> Option<(&str, &str, String)> {
+        let mut json_names: HashMap<String, &str> = HashMap::new();
+
+        for (name, field) in self.fields.iter() {
+            let json_name = field.json_name(name);
+            if let Some(other) = json_names.get(&json_name) {
+                return Some((other, name, json_name));
             }
+            json_names.insert(json_name, name);
+        }
+
+        None
+    }
+
+    /// Returns a protobuf [text format] skeleton of the message, with a placeholder value for each field.
+    /// Nested messages and enums defined inside this message are expanded, other message types are left empty.
+    /// Repeated fields are printed as a list with a single placeholder, and map fields as a single entry.
+    /// `fqn` is the fully qualified name of the message (e.g pb.hello.Foo), used to tell nested types apart
+    /// from other types with the same name
+    ///
+    /// [text format]: https://developers.google.com/protocol-buffers/docs/text-format-spec
+    pub fn to_text_skeleton(&self, fqn: &str) -> String {
+        let mut output = String::new();
+        self.write_text_skeleton(fqn, 0, &mut output);
+        output
+    }
+
+    /// Write the text format skeleton of the message fields at the given indentation
+    fn write_text_skeleton(&self, fqn: &str, indent: usize, output: &mut String) {
+        let padding = " ".repeat(indent);
+
+        for (name, field) in self.fields.iter() {
+            let type_name = field.type_name.borrow();
+
+            // look for the nested type the field refers to
+            let nested = self
+                .nested
+                .iter()
+                .map(|(name, t)| (format!("{}.{}", fqn, name), t))
+                .find(|(path, _)| refers_to(&type_name, path));
 
-            // The type was not found in the nested messages, We try to resolve it through the dependencies
-            for ns in dependencies.iter() {
-                if let Some(path) = ns.resolve_path(type_path.clone()) {
-                    *type_name = path;
-                    continue 'fields;
+            let value = match nested {
+                _ if is_scalar(&type_name) => scalar_placeholder(&type_name).to_string(),
+                Some((_, Type::Enum(e))) => e.values.keys().next().cloned().unwrap_or_default(),
+                Some((path, Type::Message(msg))) => {
+                    let mut value = String::from("{\n");
+                    msg.write_text_skeleton(&path, indent + 2, &mut value);
+                    value.push_str(&padding);
+                    value.push('}');
+                    value
+                }
+                None => String::from("{}"),
+            };
+
+            // message values are written without the `:` separator
+            let separator = match value.starts_with('{') {
+                true => " ",
+                false => ": ",
+            };
+
+            match (&field.key_type, &field.rule) {
+                (Some(key_type), _) => output.push_str(&format!(
+                    "{}{} {{ key: {} value{}{} }}\n",
+                    padding,
+                    name,
+                    scalar_placeholder(key_type),
+                    separator,
+                    value
+                )),
+                (None, Some(FieldRule::Repeated)) => {
+                    output.push_str(&format!("{}{}: [{}]\n", padding, name, value))
+                }
+                (None, _) => {
+                    output.push_str(&format!("{}{}{}{}\n", padding, name, separator, value))
                 }
             }
+        }
+    }
 
-            return Err(ResolveError::UnresolvedField {
-                type_name: type_name.to_string(),
-                field: field_name.to_string(),
-            });
+    /// Resolve and update all the types referenced inside this message to their absolute path
+    /// We iterate through the fields and the nested messages.
+    /// Unresolved types are left as-is and reported in the errors vector
+    pub fn resolve_types(
+        &self,
+        dependencies: &[&Namespace],
+        resolve_path: Vec<(&str, &LinkedHashMap<String, Type>)>,
+        errors: &mut Vec<ResolveError>,
+    ) {
+        self.for_each_field(resolve_path, &mut |field_name, field, resolve_path| {
+            let mut type_name = field.type_name.borrow_mut();
+
+            match resolve_field_type(type_name.as_str(), dependencies, resolve_path) {
+                Some(path) => *type_name = path,
+                None => errors.push(ResolveError::UnresolvedField {
+                    type_name: type_name.to_string(),
+                    field: field_name.to_string(),
+                }),
+            }
+        });
+    }
+
+    /// Call the given function for each field of this message and its nested messages,
+    /// along with the resolve path of the message that defines the field
+    pub fn for_each_field<'a, F>(
+        &'a self,
+        resolve_path: Vec<(&'a str, &'a LinkedHashMap<String, Type>)>,
+        callback: &mut F,
+    ) where
+        F: FnMut(&'a str, &'a Field, &[(&'a str, &'a LinkedHashMap<String, Type>)]),
+    {
+        for (field_name, field) in self.fields.iter() {
+            callback(field_name, field, &resolve_path);
         }
 
-        // Resolve nested messages
         for (name, t) in self.nested.iter() {
             if let Some(msg) = t.as_message() {
                 let mut resolve_path = resolve_path.clone();
                 resolve_path.push((name.as_str(), &msg.nested));
-                msg.resolve_types(dependencies, resolve_path)?;
+                msg.for_each_field(resolve_path, callback);
             }
         }
+    }
+}
+
+/// Returns the text format placeholder value of the given scalar type
+fn scalar_placeholder(type_name: &str) -> &'static str {
+    match type_name {
+        "string" | "bytes" => "\"\"",
+        "bool" => "false",
+        _ => "0",
+    }
+}
+
+/// Returns true if the given type reference refers to the type with the given fully qualified name.
+/// Absolute references (e.g .pb.Foo.Bar) must match the whole name,
+/// relative references (e.g Bar or Foo.Bar) its trailing segments, as the innermost scope takes precedence
+fn refers_to(type_name: &str, fqn: &str) -> bool {
+    match type_name.strip_prefix('.') {
+        Some(absolute) => absolute == fqn,
+        None => fqn == type_name || fqn.ends_with(&format!(".{}", type_name)),
+    }
+}
+
+/// Returns the absolute path of the given field type, or None if it can't be resolved.
+/// Scalars and absolute types (starting with a ".") are returned as-is
+///
+/// - dependencies: the namespace defining the field, followed by the namespaces it depends on
+/// - resolve_path: the path of the message defining the field, along with the nested types of each segment
+pub fn resolve_field_type(
+    type_name: &str,
+    dependencies: &[&Namespace],
+    resolve_path: &[(&str, &LinkedHashMap<String, Type>)],
+) -> Option<String> {
+    // Skip scalars
+    if is_scalar(type_name) {
+        return Some(type_name.to_string());
+    }
+
+    // The field's path (e.g pb.example.one.One.OneInner)
+    let mut type_path = type_name.split('.');
+
+    // Resolve absolute types starting with a "." by using the list of namespace dependencies
+    if type_name.starts_with('.') {
+        type_path.next(); // skip first
+        return dependencies
+            .iter()
+            .find(|ns| ns.resolve_path(type_path.clone()).is_some())
+            .map(|_| type_name.to_string());
+    }
+
+    // Walk through the resolve path backward until we resolve the type
+    // e.g if the message is defined in One.OneInner, we first try to find it in OneInner, then One, ...
+    for (index, (_, types)) in resolve_path.iter().rev().enumerate() {
+        if types.contains_path(type_path.clone()) {
+            return Some(
+                dependencies[0]
+                    .path
+                    .iter()
+                    .map(|v| v.as_str())
+                    .chain(
+                        resolve_path
+                            .iter()
+                            .take(resolve_path.len() - index)
+                            .map(|(s, _)| *s)
+                            .chain(type_path),
+                    )
+                    .collect::<Vec<_>>()
+                    .to_path_string(),
+            );
+        }
+    }
+
+    // The type was not found in the nested messages, We try to resolve it through the dependencies
+    dependencies
+        .iter()
+        .find_map(|ns| ns.resolve_path(type_path.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::test_util::{parse_test_file, parse_test_files};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_fields_sorted_by_id() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message Foo {
+          string c = 3;
+          string a = 1;
+          string d = 10;
+          string b = 2;
+        }
+        "#});
+
+        let message = root
+            .lookup_message("pb.hello.Foo")
+            .expect("Foo should exist");
+        let names = message
+            .fields_sorted_by_id()
+            .into_iter()
+            .map(|(name, field)| (name.as_str(), field.id))
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec![("a", 1), ("b", 2), ("c", 3), ("d", 10)]);
+    }
+
+    #[test]
+    fn test_serialize_source_field_names() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
 
-        Ok(())
+        message Foo {
+          string snake_case = 1;
+          string camelCase = 2;
+          string UPPER_CASE = 3;
+        }
+        "#});
+
+        let message = root
+            .lookup_message("pb.hello.Foo")
+            .expect("Foo should exist");
+        let output = serde_json::to_value(message).unwrap();
+        let names = output["fields"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["UPPER_CASE", "camelCase", "snake_case"]);
+    }
+
+    #[test]
+    fn test_to_text_skeleton() {
+        let parser = parse_test_files(&[
+            (
+                "test.proto",
+                indoc! {r#"
+                package pb.hello;
+                import "other.proto";
+
+                message Foo {
+                  string name = 1;
+                  repeated int32 ids = 2;
+                  Inner inner = 3;
+                  map<string, bool> flags = 4;
+                  Status status = 5;
+                  pb.other.Inner other = 6;
+
+                  message Inner {
+                    bool enabled = 1;
+                  }
+
+                  enum Status {
+                    UNKNOWN = 0;
+                  }
+                }
+                "#},
+            ),
+            (
+                "other.proto",
+                "package pb.other; message Inner { string name = 1; }",
+            ),
+        ]);
+        let root = parser.build_root().expect("it should build root");
+
+        let message = root
+            .lookup_message("pb.hello.Foo")
+            .expect("Foo should exist");
+
+        assert_eq!(
+            message.to_text_skeleton("pb.hello.Foo"),
+            indoc! {r#"
+            name: ""
+            ids: [0]
+            inner {
+              enabled: false
+            }
+            flags { key: "" value: false }
+            status: UNKNOWN
+            other {}
+            "#}
+        );
     }
 }
@@ -1,6 +1,6 @@
 use std::{path::Path, rc::Rc};
 
-use crate::comment::Comment;
+use crate::comment::{Comment, CommentKind};
 
 pub type ProtoOption = Vec<String>;
 
@@ -19,6 +19,13 @@ pub struct Metadata {
     pub line: usize,
 }
 
+/// Empty metadata, used for objects that are not defined in a proto file (e.g built with [NamespaceBuilder](crate::namespace::NamespaceBuilder))
+impl Default for Metadata {
+    fn default() -> Self {
+        Self::new(Path::new("").into(), None, 0)
+    }
+}
+
 impl Metadata {
     pub fn new(file_path: Rc<Path>, comment: Option<Comment>, line: usize) -> Self {
         Self {
@@ -29,8 +36,50 @@ impl Metadata {
         }
     }
 
+    /// Add an option, empty options (e.g `option;`) are ignored
     pub fn add_option(&mut self, option: ProtoOption) {
-        self.options.push(option);
+        if !option.is_empty() {
+            self.options.push(option);
+        }
+    }
+
+    /// Returns the text of the comment if any
+    pub fn comment_text(&self) -> Option<&str> {
+        self.comment.as_ref().map(|cmt| cmt.text.as_str())
+    }
+
+    /// Returns the kind of the comment (line or block) if any
+    pub fn comment_kind(&self) -> Option<CommentKind> {
+        self.comment.as_ref().map(|cmt| cmt.kind)
+    }
+
+    /// Returns the annotation tags (e.g `@example`) found in the comment. See [Comment::tags]
+    pub fn tags(&self) -> Vec<(String, String)> {
+        self.comment
+            .as_ref()
+            .map(|cmt| cmt.tags())
+            .unwrap_or_default()
+    }
+
+    /// Returns the value of the given option if it's defined (e.g `jstype` in `[jstype = JS_STRING]`)
+    pub fn option_value(&self, name: &str) -> Option<&str> {
+        self.options.iter().find_map(|option| {
+            let mut iter = option.iter();
+            iter.position(|v| v == name)?;
+            iter.next().map(|v| v.as_str())
+        })
+    }
+
+    /// Returns the value at the given path of an option, set either as a block or one value at a time.
+    /// e.g the `type` of `option (google.api.resource) = { type: "Book" };` or `option (google.api.resource).type = "Book";`
+    pub fn option_path_value(&self, name: &str, path: &str) -> Option<&str> {
+        self.options.iter().find_map(|option| {
+            let start = option.iter().position(|v| v == name)?;
+            option[start + 1..].chunks(2).find_map(|entry| match entry {
+                [key, value] if key == path => Some(value.as_str()),
+                _ => None,
+            })
+        })
     }
 
     pub fn is_deprecated(&self) -> bool {
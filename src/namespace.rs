@@ -1,17 +1,26 @@
 use crate::{
+    field::{Field, FieldRule},
     import::Import,
     into_path::{IntoPath, ToPath},
     iter_ext::IterExt,
     message::Message,
-    parse_error::ResolveError,
+    metadata::Metadata,
+    parse_error::{MergeError, ResolveError},
     r#enum::Enum,
     r#type::Type,
-    service::Service,
+    service::{Rpc, Service},
+    service_map::{self, ServiceMapConfig, ServiceTreeMap},
+    syntax::Syntax,
 };
 use linked_hash_map::LinkedHashMap;
-use serde::{ser::SerializeStruct, Serialize, Serializer};
+use serde::{
+    ser::{SerializeMap, SerializeStruct},
+    Serialize, Serializer,
+};
 use std::{
-    collections::{BTreeMap, HashSet},
+    cell::RefCell,
+    collections::{btree_map::Entry, BTreeMap, HashMap, HashSet},
+    io::Write,
     str::Split,
 };
 
@@ -27,6 +36,10 @@ pub struct Namespace {
     #[serde(skip_serializing)]
     pub imports: HashSet<Import>,
 
+    /// The syntax of the file that defined this namespace, proto2 when not declared
+    #[serde(skip_serializing)]
+    pub syntax: Syntax,
+
     /// A list of nested namespaces
     #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
     pub nested: BTreeMap<String, Namespace>,
@@ -40,6 +53,227 @@ pub struct Namespace {
     pub types: LinkedHashMap<String, Type>,
 }
 
+/// Shape statistics of a namespace tree, see [Namespace::stats]
+#[derive(Debug, Default, PartialEq)]
+pub struct NamespaceStats {
+    /// The number of namespace levels below the namespace (e.g 2 for a root containing pb.hello)
+    pub max_depth: usize,
+
+    /// The number of nested namespaces
+    pub namespace_count: usize,
+
+    /// The number of messages, including nested messages
+    pub message_count: usize,
+
+    /// The number of enums, including nested enums
+    pub enum_count: usize,
+
+    /// The number of services
+    pub service_count: usize,
+
+    /// The number of rpc methods
+    pub rpc_count: usize,
+
+    /// The number of message fields
+    pub field_count: usize,
+}
+
+impl NamespaceStats {
+    /// Add the counts of the given types and their nested types
+    fn add_types(&mut self, types: &LinkedHashMap<String, Type>) {
+        for t in types.values() {
+            match t {
+                Type::Enum(_) => self.enum_count += 1,
+                Type::Message(msg) => {
+                    self.message_count += 1;
+                    self.field_count += msg.fields.len();
+                    self.add_types(&msg.nested);
+                }
+            }
+        }
+    }
+}
+
+/// A builder used to assemble a namespace programmatically, e.g to test code generators without writing proto files.
+/// Definitions are created with empty metadata, and type names are used as-is since they are not resolved,
+/// so message and enum references should use their absolute path (e.g `.pb.hello.Bar`)
+///
+/// # Example:
+///
+/// ```
+/// use prosecco::namespace::NamespaceBuilder;
+///
+/// let root = NamespaceBuilder::new("pb.hello")
+///     .message("SayHelloRequest", |msg| {
+///         msg.field("name", "string", 1)
+///             .repeated_field("tags", "string", 2)
+///     })
+///     .build_root();
+///
+/// let msg = root.lookup_message("pb.hello.SayHelloRequest").unwrap();
+/// assert_eq!(msg.fields.len(), 2);
+/// ```
+pub struct NamespaceBuilder {
+    ns: Namespace,
+}
+
+impl NamespaceBuilder {
+    /// Returns a builder for a namespace with the given path (e.g pb.hello)
+    pub fn new(path: &str) -> Self {
+        let path = match path {
+            "" => Vec::new(),
+            path => path.into_path(),
+        };
+
+        Self {
+            ns: Namespace {
+                path,
+                ..Namespace::default()
+            },
+        }
+    }
+
+    /// Add a message, configured with the given function
+    pub fn message<F>(mut self, name: &str, build: F) -> Self
+    where
+        F: FnOnce(MessageBuilder) -> MessageBuilder,
+    {
+        let msg = build(MessageBuilder::default()).msg;
+        self.ns.add_message(name, msg);
+        self
+    }
+
+    /// Add an enum with the given (name, value) tuples
+    pub fn enumeration(mut self, name: &str, values: &[(&str, i32)]) -> Self {
+        self.ns.add_enum(name.to_string(), build_enum(values));
+        self
+    }
+
+    /// Add a rpc method to the given service, the service is created if needed
+    pub fn rpc(
+        mut self,
+        service: &str,
+        method: &str,
+        request_type: &str,
+        response_type: &str,
+    ) -> Self {
+        let rpc = Rpc::new(
+            request_type.to_string(),
+            false,
+            response_type.to_string(),
+            false,
+            Metadata::default(),
+        );
+
+        self.ns
+            .services
+            .entry(service.to_string())
+            .or_insert_with(|| Service::new(Metadata::default()))
+            .add_rpc(method.to_string(), rpc);
+        self
+    }
+
+    /// Returns the namespace
+    pub fn build(self) -> Namespace {
+        self.ns
+    }
+
+    /// Returns a root namespace containing the built namespace
+    pub fn build_root(self) -> Namespace {
+        let mut root = Namespace::default();
+        root.append_child(self.ns);
+        root
+    }
+}
+
+/// A builder used to assemble a message, see [NamespaceBuilder::message]
+#[derive(Default)]
+pub struct MessageBuilder {
+    msg: Message,
+}
+
+impl MessageBuilder {
+    /// Add a field
+    pub fn field(self, name: &str, type_name: &str, id: u32) -> Self {
+        self.add_field(name, type_name, id, None, None)
+    }
+
+    /// Add a repeated field
+    pub fn repeated_field(self, name: &str, type_name: &str, id: u32) -> Self {
+        self.add_field(name, type_name, id, Some(FieldRule::Repeated), None)
+    }
+
+    /// Add a map field
+    pub fn map_field(self, name: &str, key_type: &str, type_name: &str, id: u32) -> Self {
+        self.add_field(name, type_name, id, None, Some(key_type.to_string()))
+    }
+
+    /// Add a nested message, configured with the given function
+    pub fn message<F>(mut self, name: &str, build: F) -> Self
+    where
+        F: FnOnce(MessageBuilder) -> MessageBuilder,
+    {
+        let msg = build(MessageBuilder::default()).msg;
+        self.msg.add_nested_message(name.to_string(), msg);
+        self
+    }
+
+    /// Add a nested enum with the given (name, value) tuples
+    pub fn enumeration(mut self, name: &str, values: &[(&str, i32)]) -> Self {
+        self.msg
+            .add_nested_enum(name.to_string(), build_enum(values));
+        self
+    }
+
+    fn add_field(
+        mut self,
+        name: &str,
+        type_name: &str,
+        id: u32,
+        rule: Option<FieldRule>,
+        key_type: Option<String>,
+    ) -> Self {
+        let field = Field::new(
+            id,
+            type_name.to_string(),
+            rule,
+            key_type,
+            Metadata::default(),
+        );
+        self.msg.add_field(name.to_string(), field);
+        self
+    }
+}
+
+/// Returns an enum with the given (name, value) tuples
+fn build_enum(values: &[(&str, i32)]) -> Enum {
+    let mut e = Enum::new(Metadata::default());
+    for (name, value) in values {
+        e.insert(name.to_string(), *value);
+    }
+    e
+}
+
+/// Collect the fields of the given message and its nested messages
+fn collect_message_fields<'a>(
+    msg: &'a Message,
+    path: &mut Vec<&'a str>,
+    fields: &mut Vec<(String, &'a String, &'a Field)>,
+) {
+    let owner = path.join(".");
+    for (name, field) in msg.fields.iter() {
+        fields.push((owner.clone(), name, field));
+    }
+
+    for (name, t) in msg.nested.iter() {
+        if let Type::Message(nested) = t {
+            path.push(name);
+            collect_message_fields(nested, path, fields);
+            path.pop();
+        }
+    }
+}
+
 /// Wrap the namespace into a wrapper struct to match the serialization format of protobuf.js
 impl Serialize for Namespace {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -55,7 +289,7 @@ impl Serialize for Namespace {
             where
                 S: Serializer,
             {
-                Namespace::serialize(&self.root, serializer)
+                Namespace::serialize(self.root, serializer)
             }
         }
 
@@ -71,6 +305,7 @@ impl Namespace {
         Self {
             path: path.into_path(),
             imports: HashSet::new(),
+            syntax: Syntax::default(),
             nested: BTreeMap::new(),
             types: LinkedHashMap::new(),
             services: LinkedHashMap::new(),
@@ -100,6 +335,20 @@ impl Namespace {
         self.services.insert(name, service);
     }
 
+    /// Returns the gRPC path of a method defined in this namespace (e.g /pb.hello/SayHello).
+    /// Methods of package-less files are served at the root (e.g /SayHello)
+    pub fn grpc_path(&self, method_name: &str) -> String {
+        match self.path.is_empty() {
+            true => format!("/{}", method_name),
+            false => format!("/{}/{}", self.path.join("."), method_name),
+        }
+    }
+
+    /// Returns the service tree map of this namespace, see [crate::service_map::create_with]
+    pub fn to_service_map(&self, config: &ServiceMapConfig) -> ServiceTreeMap<'_> {
+        service_map::create_with(self, config)
+    }
+
     /// Find the child for the given path
     pub fn child(&self, path: &str) -> Option<&Namespace> {
         let paths = path.split('.');
@@ -115,6 +364,220 @@ impl Namespace {
         Some(ptr)
     }
 
+    /// Returns the path of every nested namespace that directly defines types or services (e.g pb.hello)
+    pub fn packages(&self) -> Vec<String> {
+        let mut packages = Vec::new();
+        self.collect_packages(&mut Vec::new(), &mut packages);
+        packages
+    }
+
+    /// Walk the nested namespaces and collect the path of the ones defining types or services
+    fn collect_packages<'a>(&'a self, path: &mut Vec<&'a str>, packages: &mut Vec<String>) {
+        for (name, child) in self.nested.iter() {
+            path.push(name);
+
+            if !child.types.is_empty() || !child.services.is_empty() {
+                packages.push(path.join("."));
+            }
+
+            child.collect_packages(path, packages);
+            path.pop();
+        }
+    }
+
+    /// Returns every message field of the tree, along with the fully qualified name of the message defining it
+    pub fn all_fields(&self) -> impl Iterator<Item = (String, &String, &Field)> {
+        let mut fields = Vec::new();
+        self.collect_fields(&mut Vec::new(), &mut fields);
+        fields.into_iter()
+    }
+
+    /// Walk the nested namespaces and collect the fields of their messages
+    fn collect_fields<'a>(
+        &'a self,
+        path: &mut Vec<&'a str>,
+        fields: &mut Vec<(String, &'a String, &'a Field)>,
+    ) {
+        for (name, t) in self.types.iter() {
+            if let Type::Message(msg) = t {
+                path.push(name);
+                collect_message_fields(msg, path, fields);
+                path.pop();
+            }
+        }
+
+        for (name, child) in self.nested.iter() {
+            path.push(name);
+            child.collect_fields(path, fields);
+            path.pop();
+        }
+    }
+
+    /// Returns the fully qualified names of the messages and rpcs that reference the given type (e.g pb.foo.Bar),
+    /// through a field (including repeated and map fields) or an rpc request or response.
+    /// Rpcs are named after their service (e.g pb.hello.HelloWorld.SayHello).
+    /// Note: this must be called once the types have been resolved
+    pub fn referencers(&self, fqn: &str) -> Vec<String> {
+        let type_name = format!(".{}", fqn.strip_prefix('.').unwrap_or(fqn));
+
+        let mut referencers = self
+            .all_fields()
+            .filter(|(_, _, field)| *field.type_name.borrow() == type_name)
+            .map(|(owner, _, _)| owner)
+            .collect::<Vec<_>>();
+
+        // fields are grouped by message, a message referencing the type with several fields is listed once
+        referencers.dedup();
+
+        self.collect_rpc_referencers(&type_name, &mut Vec::new(), &mut referencers);
+        referencers
+    }
+
+    /// Walk the nested namespaces and collect the rpcs using the given type as request or response
+    fn collect_rpc_referencers<'a>(
+        &'a self,
+        type_name: &str,
+        path: &mut Vec<&'a str>,
+        referencers: &mut Vec<String>,
+    ) {
+        for (service_name, service) in self.services.iter() {
+            for (method_name, rpc) in service.methods.iter() {
+                if *rpc.request_type.borrow() == type_name
+                    || *rpc.response_type.borrow() == type_name
+                {
+                    let mut rpc_path = path.clone();
+                    rpc_path.push(service_name);
+                    rpc_path.push(method_name);
+                    referencers.push(rpc_path.join("."));
+                }
+            }
+        }
+
+        for (name, child) in self.nested.iter() {
+            path.push(name);
+            child.collect_rpc_referencers(type_name, path, referencers);
+            path.pop();
+        }
+    }
+
+    /// Find the service for the given fully qualified name (e.g pb.hello.HelloWorld)
+    pub fn find_service(&self, fqn: &str) -> Option<&Service> {
+        let fqn = fqn.strip_prefix('.').unwrap_or(fqn);
+        match fqn.rsplit_once('.') {
+            Some((package, name)) => self.child(package)?.services.get(name),
+            None => self.services.get(fqn),
+        }
+    }
+
+    /// Find the rpc method of the service matching the given fully qualified name
+    pub fn find_rpc(&self, service_fqn: &str, method: &str) -> Option<&Rpc> {
+        self.find_service(service_fqn)?.methods.get(method)
+    }
+
+    /// Find the type for the given fully qualified name (e.g .pb.hello.SayHelloRequest)
+    pub fn lookup_type(&self, fqn: &str) -> Option<&Type> {
+        let fqn = fqn.strip_prefix('.').unwrap_or(fqn);
+        self.lookup_type_path(fqn.split('.'))
+    }
+
+    /// Find the message for the given fully qualified name (e.g .pb.hello.SayHelloRequest)
+    pub fn lookup_message(&self, fqn: &str) -> Option<&Message> {
+        self.lookup_type(fqn).and_then(Type::as_message)
+    }
+
+    /// Find the request and response messages of the given rpc
+    pub fn lookup_rpc_messages(&self, rpc: &Rpc) -> Option<(&Message, &Message)> {
+        let request = self.lookup_message(rpc.request_type.borrow().as_str())?;
+        let response = self.lookup_message(rpc.response_type.borrow().as_str())?;
+        Some((request, response))
+    }
+
+    /// Walk the nested namespaces and then the nested types to find the type matching the given path
+    fn lookup_type_path(&self, mut path: Split<char>) -> Option<&Type> {
+        let name = path.next()?;
+
+        if let Some(found) = self
+            .nested
+            .get(name)
+            .and_then(|child| child.lookup_type_path(path.clone()))
+        {
+            return Some(found);
+        }
+
+        let mut found_type = self.types.get(name)?;
+        for name in path {
+            found_type = found_type.get(name)?;
+        }
+
+        Some(found_type)
+    }
+
+    /// Move the `from` package (e.g pb.old) and its sub packages to the `to` package (e.g pb.new).
+    /// Both the namespace paths and the resolved type references are rewritten.
+    /// Note: this must be called once the types have been resolved, and before the namespace is appended to the root
+    pub fn apply_package_alias(&mut self, from: &str, to: &str) {
+        let from_path = from.into_path();
+        if self.path.starts_with(&from_path) {
+            self.path.splice(..from_path.len(), to.into_path());
+        }
+
+        let alias = |type_ref: &RefCell<String>| {
+            let renamed = type_ref
+                .borrow()
+                .strip_prefix('.')
+                .and_then(|type_name| type_name.strip_prefix(from))
+                .filter(|rest| rest.starts_with('.'))
+                .map(|rest| format!(".{}{}", to, rest));
+
+            if let Some(renamed) = renamed {
+                type_ref.replace(renamed);
+            }
+        };
+
+        for (name, msg) in self
+            .types
+            .iter()
+            .filter_map(|(n, t)| Some((n, t.as_message()?)))
+        {
+            msg.for_each_field(vec![(name.as_str(), &msg.nested)], &mut |_, field, _| {
+                alias(&field.type_name)
+            });
+        }
+
+        for rpc in self.services.values().flat_map(|s| s.methods.values()) {
+            alias(&rpc.request_type);
+            alias(&rpc.response_type);
+        }
+
+        for child in self.nested.values_mut() {
+            child.apply_package_alias(from, to);
+        }
+    }
+
+    /// Returns the shape statistics of this namespace and its nested namespaces, walking the tree once
+    pub fn stats(&self) -> NamespaceStats {
+        let mut stats = NamespaceStats::default();
+        self.collect_stats(0, &mut stats);
+        stats
+    }
+
+    /// Walk the namespace tree and add the counts of each namespace to the stats
+    fn collect_stats(&self, depth: usize, stats: &mut NamespaceStats) {
+        stats.max_depth = stats.max_depth.max(depth);
+        stats.service_count += self.services.len();
+        stats.rpc_count += self
+            .services
+            .values()
+            .map(|s| s.methods.len())
+            .sum::<usize>();
+        stats.add_types(&self.types);
+
+        for child in self.nested.values() {
+            stats.namespace_count += 1;
+            child.collect_stats(depth + 1, stats);
+        }
+    }
+
     /// Append a child to the current namespace.
     /// If there is already a namespace with the same path, it will be merged with child
     pub fn append_child(&mut self, child: Namespace) {
@@ -128,10 +591,7 @@ impl Namespace {
         } = child;
 
         for key in path.iter() {
-            ptr = ptr
-                .nested
-                .entry(key.into())
-                .or_insert_with(Namespace::default)
+            ptr = ptr.nested.entry(key.into()).or_default()
         }
 
         ptr.path = path;
@@ -139,8 +599,134 @@ impl Namespace {
         ptr.services.extend(services);
     }
 
+    /// Deep merge another root namespace into this one, e.g to combine roots built in shards.
+    /// Returns an error, and leaves this namespace unchanged, if both define the same name, as a type, a service or a
+    /// nested namespace
+    pub fn merge_root(&mut self, other: Namespace) -> Result<(), MergeError> {
+        self.check_merge_conflicts(&other, &mut Vec::new())?;
+        self.merge(other);
+        Ok(())
+    }
+
+    /// Returns an error if the other namespace defines a name already defined in this one.
+    /// Nested namespaces are merged together, but can't share their name with a type or service
+    fn check_merge_conflicts<'a>(
+        &self,
+        other: &'a Namespace,
+        path: &mut Vec<&'a str>,
+    ) -> Result<(), MergeError> {
+        let fqn = |name: &str| {
+            path.iter()
+                .copied()
+                .chain(std::iter::once(name))
+                .collect::<Vec<_>>()
+                .join(".")
+        };
+
+        let is_defined = |ns: &Namespace, name: &str| {
+            ns.types.contains_key(name)
+                || ns.services.contains_key(name)
+                || ns.nested.contains_key(name)
+        };
+
+        for name in other.types.keys() {
+            if self.types.contains_key(name) {
+                return Err(MergeError::DuplicateType(fqn(name)));
+            }
+        }
+
+        for name in other.services.keys() {
+            if self.services.contains_key(name) {
+                return Err(MergeError::DuplicateService(fqn(name)));
+            }
+        }
+
+        if let Some(name) = other
+            .types
+            .keys()
+            .chain(other.services.keys())
+            .find(|name| is_defined(self, name))
+            .or_else(|| {
+                other.nested.keys().find(|name| {
+                    self.types.contains_key(*name) || self.services.contains_key(*name)
+                })
+            })
+        {
+            return Err(MergeError::NameConflict(fqn(name)));
+        }
+
+        for (name, child) in other.nested.iter() {
+            if let Some(own_child) = self.nested.get(name) {
+                path.push(name);
+                own_child.check_merge_conflicts(child, path)?;
+                path.pop();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recursively move the content of the other namespace into this one
+    fn merge(&mut self, other: Namespace) {
+        let Namespace {
+            path,
+            imports,
+            nested,
+            services,
+            types,
+            ..
+        } = other;
+
+        if self.path.is_empty() {
+            self.path = path;
+        }
+
+        self.imports.extend(imports);
+        self.types.extend(types);
+        self.services.extend(services);
+
+        for (name, child) in nested {
+            match self.nested.entry(name) {
+                Entry::Vacant(entry) => {
+                    entry.insert(child);
+                }
+                Entry::Occupied(mut entry) => entry.get_mut().merge(child),
+            }
+        }
+    }
+
+    /// Returns true if the namespace and its nested namespaces don't define any type or service
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+            && self.services.is_empty()
+            && self.nested.values().all(Self::is_empty)
+    }
+
+    /// Recursively remove the nested namespaces that don't define any type or service
+    pub fn prune_empty(&mut self) {
+        self.nested.retain(|_, child| {
+            child.prune_empty();
+            !child.is_empty()
+        });
+    }
+
     /// Resolve and update all the types referenced inside this namespace to their absolute path
     pub fn resolve_types(&self, dependencies: Vec<&Namespace>) -> Result<(), ResolveError> {
+        match self
+            .resolve_types_collect_errors(dependencies)
+            .into_iter()
+            .next()
+        {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Resolve and update all the types referenced inside this namespace to their absolute path.
+    /// Unlike [Namespace::resolve_types], resolution continues past failures:
+    /// unresolved types are left as-is and every failure is returned
+    pub fn resolve_types_collect_errors(&self, dependencies: Vec<&Namespace>) -> Vec<ResolveError> {
+        let mut errors = Vec::new();
         let dependencies: Vec<_> = dependencies.into_iter().start_with(self).collect();
 
         // loop through all the types in the namespace
@@ -151,7 +737,11 @@ impl Namespace {
                 Type::Message(msg) => msg,
             };
 
-            msg.resolve_types(&dependencies, [(name.as_str(), &msg.nested)].into())?
+            msg.resolve_types(
+                &dependencies,
+                [(name.as_str(), &msg.nested)].into(),
+                &mut errors,
+            )
         }
 
         // loop through all the services rpc request and response types
@@ -171,10 +761,29 @@ impl Namespace {
                 }
             }
 
-            return Err(ResolveError::UnresolvedRpcType(type_ref.to_string()));
+            errors.push(ResolveError::UnresolvedRpcType(type_ref.to_string()));
         }
 
-        Ok(())
+        errors
+    }
+
+    /// Find the cycles of messages that contain each other through proto2 `required` message fields.
+    /// Such messages can't be instantiated, since they would have an infinite size.
+    /// Each cycle is returned as the list of fully qualified message names that compose it.
+    /// Note: this should be called on the root namespace, once all the types have been resolved
+    pub fn check_required_cycles(&self) -> Vec<Vec<String>> {
+        let mut edges = BTreeMap::new();
+        collect_required_edges(self, "", &mut edges);
+
+        let mut visited = HashMap::new();
+        let mut stack = Vec::new();
+        let mut cycles = Vec::new();
+
+        for node in edges.keys() {
+            find_cycles(node, &edges, &mut visited, &mut stack, &mut cycles);
+        }
+
+        cycles
     }
 
     /// Resolve the path against the namespace and return the absolute path when found
@@ -207,11 +816,144 @@ impl Namespace {
     }
 }
 
+/// Write the descriptors of the root namespace as pretty printed JSON into the given writer.
+/// Unlike `serde_json::to_string_pretty`, the output is streamed and never held entirely in memory
+pub fn write_descriptors<W: Write>(root: &Namespace, writer: W) -> serde_json::Result<()> {
+    serde_json::to_writer_pretty(writer, root)
+}
+
+/// Write the descriptors of the root namespace as pretty printed JSON into the given writer,
+/// using the given options. See [write_descriptors]
+pub fn write_descriptors_with<W: Write>(
+    root: &Namespace,
+    writer: W,
+    options: &DescriptorOptions,
+) -> serde_json::Result<()> {
+    serde_json::to_writer_pretty(writer, &Descriptors { ns: root, options })
+}
+
+/// Options controlling the descriptors output
+#[derive(Debug, Clone, Default)]
+pub struct DescriptorOptions {
+    /// Omit the services from the descriptors, for clients that only encode and decode messages
+    pub skip_services: bool,
+}
+
+/// Serialize a namespace as descriptors using the given options
+struct Descriptors<'a> {
+    ns: &'a Namespace,
+    options: &'a DescriptorOptions,
+}
+
+impl<'a> Serialize for Descriptors<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if !self.options.skip_services {
+            return self.ns.serialize(serializer);
+        }
+
+        /// Serialize the nested namespaces and types, skipping the services
+        struct Nested<'a>(&'a Descriptors<'a>);
+
+        impl<'a> Serialize for Nested<'a> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let Descriptors { ns, options } = self.0;
+                let mut map = serializer.serialize_map(None)?;
+                for (name, child) in ns.nested.iter() {
+                    map.serialize_entry(name, &Descriptors { ns: child, options })?;
+                }
+                for (name, t) in ns.types.iter() {
+                    map.serialize_entry(name, t)?;
+                }
+                map.end()
+            }
+        }
+
+        let mut state = serializer.serialize_struct("Wrapper", 1)?;
+        state.serialize_field("nested", &Nested(self))?;
+        state.end()
+    }
+}
+
+/// Collect the required message fields of every message as edges: message => [field types]
+fn collect_required_edges(ns: &Namespace, prefix: &str, edges: &mut BTreeMap<String, Vec<String>>) {
+    collect_required_type_edges(&ns.types, prefix, edges);
+
+    for (name, child) in ns.nested.iter() {
+        collect_required_edges(child, &format!("{}.{}", prefix, name), edges);
+    }
+}
+
+/// Collect the required message fields of the given types and their nested types
+fn collect_required_type_edges(
+    types: &LinkedHashMap<String, Type>,
+    prefix: &str,
+    edges: &mut BTreeMap<String, Vec<String>>,
+) {
+    for (name, t) in types.iter() {
+        let msg = match t {
+            Type::Enum(_) => continue,
+            Type::Message(msg) => msg,
+        };
+
+        let fqn = format!("{}.{}", prefix, name);
+        let targets = msg
+            .fields
+            .values()
+            .filter(|field| field.rule == Some(FieldRule::Required) && field.key_type.is_none())
+            .map(|field| field.type_name.borrow().to_string())
+            .collect();
+
+        collect_required_type_edges(&msg.nested, &fqn, edges);
+        edges.insert(fqn, targets);
+    }
+}
+
+/// Depth first search used to find the cycles in the required fields graph
+fn find_cycles<'a>(
+    node: &'a str,
+    edges: &'a BTreeMap<String, Vec<String>>,
+    visited: &mut HashMap<&'a str, bool>,
+    stack: &mut Vec<&'a str>,
+    cycles: &mut Vec<Vec<String>>,
+) {
+    // visited maps a node to true while it's on the stack, and false once done
+    if visited.contains_key(node) {
+        return;
+    }
+
+    visited.insert(node, true);
+    stack.push(node);
+
+    for target in edges.get(node).into_iter().flatten() {
+        match visited.get(target.as_str()) {
+            Some(true) => {
+                let start = stack.iter().position(|n| n == target).unwrap_or(0);
+                cycles.push(stack[start..].iter().map(|n| n.to_string()).collect());
+            }
+            Some(false) => {}
+            None => find_cycles(target, edges, visited, stack, cycles),
+        }
+    }
+
+    stack.pop();
+    visited.insert(node, false);
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
-    use crate::{message::Message, metadata::Metadata, namespace::Namespace};
+    use crate::{
+        message::Message, metadata::Metadata, namespace::Namespace, parse_error::MergeError,
+        parser::test_util::parse_test_file,
+    };
+    use indoc::indoc;
 
     #[test]
     fn test_add_child() {
@@ -227,6 +969,239 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_merge_root() {
+        let mut root = parse_test_file(indoc! {r#"
+        package pb.foo;
+        message Foo {}
+        "#});
+
+        let other = parse_test_file(indoc! {r#"
+        package pb.foo.bar;
+        message Bar {}
+        service BarService {}
+        "#});
+
+        root.merge_root(other).expect("it should merge roots");
+        assert!(root.lookup_message("pb.foo.Foo").is_some());
+        assert!(root.lookup_message("pb.foo.bar.Bar").is_some());
+        assert!(root
+            .child("pb.foo.bar")
+            .map(|ns| ns.services.contains_key("BarService"))
+            .unwrap_or(false));
+
+        let conflicting = parse_test_file(indoc! {r#"
+        package pb.foo;
+        message Baz {}
+        message Foo {}
+        "#});
+
+        assert_eq!(
+            root.merge_root(conflicting),
+            Err(MergeError::DuplicateType("pb.foo.Foo".to_string()))
+        );
+        assert!(root.lookup_message("pb.foo.Baz").is_none());
+
+        let conflicting = parse_test_file(indoc! {r#"
+        package pb.foo.bar;
+        service BarService {}
+        "#});
+
+        assert_eq!(
+            root.merge_root(conflicting),
+            Err(MergeError::DuplicateService(
+                "pb.foo.bar.BarService".to_string()
+            ))
+        );
+
+        let conflicting = parse_test_file(indoc! {r#"
+        package pb.foo;
+        message bar {}
+        "#});
+
+        assert_eq!(
+            root.merge_root(conflicting),
+            Err(MergeError::NameConflict("pb.foo.bar".to_string()))
+        );
+
+        let conflicting = parse_test_file(indoc! {r#"
+        package pb.foo.Foo;
+        message Qux {}
+        "#});
+
+        assert_eq!(
+            root.merge_root(conflicting),
+            Err(MergeError::NameConflict("pb.foo.Foo".to_string()))
+        );
+        assert!(root.lookup_message("pb.foo.Foo.Qux").is_none());
+    }
+
+    #[test]
+    fn test_all_fields() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message Foo {
+          string name = 1;
+          bytes data = 2;
+          message Inner {
+            bytes raw = 1;
+          }
+        }
+
+        message Bar {
+          int32 id = 1;
+        }
+        "#});
+
+        let fields = root
+            .all_fields()
+            .map(|(owner, name, _)| format!("{}.{}", owner, name))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            fields,
+            vec![
+                "pb.hello.Foo.name",
+                "pb.hello.Foo.data",
+                "pb.hello.Foo.Inner.raw",
+                "pb.hello.Bar.id"
+            ]
+        );
+
+        let bytes_fields = root
+            .all_fields()
+            .filter(|(_, _, field)| *field.type_name.borrow() == "bytes")
+            .count();
+        assert_eq!(bytes_fields, 2);
+    }
+
+    #[test]
+    fn test_lookup_message() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        service HelloWorld {
+          rpc SayHello (SayHelloRequest) returns (SayHelloResponse) {}
+        }
+
+        message SayHelloRequest {
+          string name = 1;
+          message Inner {}
+        }
+
+        message SayHelloResponse {}
+        "#});
+
+        let request = root
+            .lookup_message(".pb.hello.SayHelloRequest")
+            .expect("request should be found");
+        assert!(request.fields.contains_key("name"));
+
+        assert!(root
+            .lookup_message("pb.hello.SayHelloRequest.Inner")
+            .is_some());
+        assert!(root.lookup_message("pb.hello.Missing").is_none());
+
+        let rpc = root
+            .child("pb.hello")
+            .and_then(|ns| ns.services.get("HelloWorld"))
+            .and_then(|service| service.methods.get("SayHello"))
+            .expect("rpc should be found");
+
+        let (request, response) = root
+            .lookup_rpc_messages(rpc)
+            .expect("rpc messages should be found");
+        assert!(request.fields.contains_key("name"));
+        assert!(response.fields.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_forward_references() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message First {
+          Second second = 1;
+          message Inner {
+            Third third = 1;
+            Third.Nested nested = 2;
+          }
+        }
+
+        message Second {}
+
+        message Third {
+          message Nested {}
+        }
+        "#});
+
+        let type_of = |message: &str, field: &str| {
+            root.lookup_message(message)
+                .and_then(|msg| msg.fields.get(field))
+                .map(|field| field.type_name.borrow().to_string())
+        };
+
+        assert_eq!(
+            type_of("pb.hello.First", "second").as_deref(),
+            Some(".pb.hello.Second")
+        );
+        assert_eq!(
+            type_of("pb.hello.First.Inner", "third").as_deref(),
+            Some(".pb.hello.Third")
+        );
+        assert_eq!(
+            type_of("pb.hello.First.Inner", "nested").as_deref(),
+            Some(".pb.hello.Third.Nested")
+        );
+    }
+
+    #[test]
+    fn test_packages() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        service HelloWorld {
+          rpc SayHello (SayHelloRequest) returns (SayHelloResponse) {}
+        }
+
+        message SayHelloRequest {}
+        message SayHelloResponse {}
+        "#});
+
+        assert_eq!(root.packages(), vec!["pb.hello".to_string()]);
+    }
+
+    #[test]
+    fn test_find_service_and_rpc() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello.world;
+
+        service HelloWorld {
+          rpc SayHello (SayHelloRequest) returns (SayHelloResponse) {}
+        }
+
+        message SayHelloRequest {}
+        message SayHelloResponse {}
+        "#});
+
+        assert!(root.find_service("pb.hello.world.HelloWorld").is_some());
+        assert!(root.find_service(".pb.hello.world.HelloWorld").is_some());
+        assert!(root.find_service("pb.hello.HelloWorld").is_none());
+        assert!(root.find_service("HelloWorld").is_none());
+
+        let rpc = root
+            .find_rpc("pb.hello.world.HelloWorld", "SayHello")
+            .expect("rpc should be found");
+        assert_eq!(
+            *rpc.request_type.borrow(),
+            ".pb.hello.world.SayHelloRequest"
+        );
+        assert!(root
+            .find_rpc("pb.hello.world.HelloWorld", "Missing")
+            .is_none());
+    }
+
     #[test]
     fn test_resolve_path() {
         let mut ns = Namespace::new("pb.foo.bar");
@@ -237,4 +1212,171 @@ mod tests {
         let path = ns.resolve_path("Bar".split('.'));
         assert_eq!(path, Some(".pb.foo.bar.Bar".into()))
     }
+
+    #[test]
+    fn test_check_required_cycles() {
+        let root = parse_test_file(indoc! {r#"
+        syntax = "proto2";
+        package pb.x;
+
+        message Node {
+          required Node next = 1;
+        }
+
+        message Tree {
+          optional Tree left = 1;
+          repeated Tree children = 2;
+        }
+
+        message A {
+          required B b = 1;
+        }
+
+        message B {
+          required A a = 1;
+        }
+        "#});
+
+        assert_eq!(
+            root.check_required_cycles(),
+            vec![
+                vec![".pb.x.A".to_string(), ".pb.x.B".to_string()],
+                vec![".pb.x.Node".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_descriptors() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message SayHelloRequest {
+          string name = 1;
+        }
+        "#});
+
+        let mut output = Vec::new();
+        super::write_descriptors(&root, &mut output).expect("it should write descriptors");
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            serde_json::to_string_pretty(&root).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_descriptors_without_services() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        service HelloWorld {
+          rpc SayHello (SayHelloRequest) returns (SayHelloRequest) {}
+        }
+
+        message SayHelloRequest {
+          string name = 1;
+        }
+        "#});
+
+        let options = super::DescriptorOptions {
+            skip_services: true,
+        };
+        let mut output = Vec::new();
+        super::write_descriptors_with(&root, &mut output, &options)
+            .expect("it should write descriptors");
+
+        let output: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let hello = &output["nested"]["pb"]["nested"]["hello"]["nested"];
+
+        assert!(hello.get("HelloWorld").is_none());
+        assert!(hello.get("SayHelloRequest").is_some());
+        assert!(root.find_service("pb.hello.HelloWorld").is_some());
+    }
+
+    #[test]
+    fn test_referencers() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        service HelloWorld {
+          rpc SayHello (SayHelloRequest) returns (Greeting) {}
+          rpc Ping (SayHelloRequest) returns (SayHelloRequest) {}
+        }
+
+        message Greeting {
+          string text = 1;
+        }
+
+        message SayHelloRequest {
+          string name = 1;
+        }
+
+        message Inbox {
+          repeated Greeting greetings = 1;
+          Greeting pinned = 2;
+
+          message Thread {
+            map<string, Greeting> by_author = 1;
+          }
+        }
+
+        message Archive {
+          Greeting last = 1;
+        }
+        "#});
+
+        assert_eq!(
+            root.referencers("pb.hello.Greeting"),
+            vec![
+                "pb.hello.Inbox",
+                "pb.hello.Inbox.Thread",
+                "pb.hello.Archive",
+                "pb.hello.HelloWorld.SayHello",
+            ]
+        );
+        assert_eq!(
+            root.referencers(".pb.hello.SayHelloRequest"),
+            vec!["pb.hello.HelloWorld.SayHello", "pb.hello.HelloWorld.Ping"]
+        );
+        assert!(root.referencers("pb.hello.Archive").is_empty());
+    }
+
+    #[test]
+    fn test_stats() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        service HelloWorld {
+          rpc SayHello (SayHelloRequest) returns (SayHelloResponse) {}
+          rpc LotsOfGreetings(stream SayHelloRequest) returns (SayHelloResponse) {}
+        }
+
+        message SayHelloRequest {
+          string name = 1;
+          Status status = 2;
+
+          enum Status {
+            UNKNOWN = 0;
+          }
+        }
+
+        message SayHelloResponse {
+          string hello = 1;
+        }
+        "#});
+
+        assert_eq!(
+            root.stats(),
+            super::NamespaceStats {
+                max_depth: 2,
+                namespace_count: 2,
+                message_count: 2,
+                enum_count: 1,
+                service_count: 1,
+                rpc_count: 2,
+                field_count: 3,
+            }
+        );
+    }
 }
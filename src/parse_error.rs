@@ -1,11 +1,12 @@
-use crate::{position::Position, token::Token};
-use std::{io, num::ParseIntError, path::PathBuf};
+use crate::{field::FieldRule, position::Position, token::Token};
+use std::{borrow::Cow, io, num::ParseIntError, path::PathBuf};
 use thiserror::Error;
 
 /// TokenError defines an error generated by the Tokenizer
-#[derive(Error, Debug, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Error, Debug, Clone, PartialEq)]
 #[error("...")]
-pub enum TokenError {    
+pub enum TokenError {
     #[error("Invalid end delimiter {0}")]
     MissingEndDelimiter(char),
 
@@ -17,6 +18,7 @@ pub enum TokenError {
 }
 
 /// ParseError defines an error generated when parsing a file
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Error, Debug, PartialEq)]
 #[error("...")]
 pub enum ParseError {
@@ -26,6 +28,9 @@ pub enum ParseError {
     #[error("proto version {0} not supported")]
     ProtoSyntaxNotSupported(String),
 
+    #[error("syntax version must be quoted: syntax = \"{0}\";")]
+    UnquotedSyntax(String),
+
     #[error("package already set")]
     PackageAlreadySet,
 
@@ -44,9 +49,55 @@ pub enum ParseError {
     #[error("failed to parse field id: {0}")]
     ParseFieldId(ParseIntError),
 
+    #[error("field rule \"{0}\" is not allowed on map fields")]
+    RuleOnMapField(FieldRule),
+
+    #[error("field number {0} is reserved or out of range")]
+    ReservedFieldNumber(u32),
+
+    #[error("failed to parse reserved range: {0}")]
+    ParseReservedRange(ParseIntError),
+
+    #[error("field {0} uses a reserved number or name")]
+    UseOfReservedField(String),
+
+    #[error("enum value {0} uses a reserved number or name")]
+    UseOfReservedEnumValue(String),
+
+    #[error("the first value of enum {0} must be zero in proto3")]
+    Proto3EnumMissingZero(String),
+
+    #[error("field {field} is required, required fields are forbidden")]
+    RequiredFieldForbidden { field: String },
+
+    #[error("rpc {0} is missing its returns (...) clause")]
+    MissingRpcReturns(String),
+
+    #[error("unexpected statement \"{found}\" in rpc {method}, only options are allowed")]
+    UnexpectedRpcStatement { method: String, found: Token },
+
+    #[error("rpc {method} is already defined in service {service}")]
+    DuplicateRpc { service: String, method: String },
+
+    #[error("service {0} is already defined")]
+    DuplicateService(String),
+
+    #[error("fields {first} and {second} have the same JSON name {json_name}")]
+    JsonNameCollision {
+        first: String,
+        second: String,
+        json_name: String,
+    },
+
     #[error("failed to parse enum value: {0}")]
     ParseEnumValue(ParseIntError),
 
+    #[error("enum value {0} is out of the int32 range")]
+    EnumValueOutOfRange(String),
+
+    #[error("extend {0} is not supported in strict mode")]
+    UnsupportedExtend(String),
+
     #[error("{0}")]
     TokenError(#[from] TokenError),
 }
@@ -63,6 +114,26 @@ pub enum ResolveError {
 
     #[error("Failed to resolve rpc type: {_0}")]
     UnresolvedRpcType(String),
+
+    #[error("Missing import for type {type_name} of field {field}, defined in {file}")]
+    MissingImportForType {
+        type_name: String,
+        field: String,
+        file: PathBuf,
+    },
+}
+
+/// MergeError defines an error generated when merging root namespaces
+#[derive(Error, Debug, PartialEq)]
+pub enum MergeError {
+    #[error("type {0} is defined in both namespaces")]
+    DuplicateType(String),
+
+    #[error("service {0} is defined in both namespaces")]
+    DuplicateService(String),
+
+    #[error("{0} is defined in both namespaces as a different kind of definition")]
+    NameConflict(String),
 }
 
 impl ResolveError {
@@ -81,12 +152,43 @@ pub enum ParseFileError {
     #[error("File {0}, {1}")]
     Resolve(PathBuf, ResolveError),
 
+    #[error("File {path}, import {import} is outside of the root directory")]
+    ImportEscapesRoot {
+        path: PathBuf,
+        import: PathBuf,
+    },
+
+    #[error("File {path} is not valid UTF-8 at byte {byte_offset}")]
+    InvalidUtf8 {
+        path: PathBuf,
+        byte_offset: usize,
+    },
+
+    #[error("File {path} is too large ({size} bytes)")]
+    FileTooLarge {
+        path: PathBuf,
+        size: usize,
+    },
+
+    #[error("Failed to write file {0}. {1}")]
+    Write(PathBuf, io::Error),
+
+    #[error("Invalid cache file {0}. {1}")]
+    InvalidCache(PathBuf, serde_json::Error),
+
     #[error("{0}")]
     ParseError(String),
 
     FileAlreadyParsed,
 }
 
+/// The maximum number of characters of the context lines rendered in parse errors.
+/// Longer lines (e.g minified files) are truncated to a window around the error column
+pub const MAX_CONTEXT_LINE_WIDTH: usize = 100;
+
+/// The marker printed in place of the truncated part of a context line
+const ELLIPSIS: &str = "...";
+
 impl ParseErrorWithPosition {
     /// Returns a ParseFileError by using the file's content and current position
     pub fn into_file_error(self, file_path: PathBuf, content: &str) -> ParseFileError {
@@ -99,19 +201,39 @@ impl ParseErrorWithPosition {
             .split('\n')
             .skip(position.line - show_lines)
             .take(show_lines)
+            .collect::<Vec<_>>();
+
+        // the first column of the rendered window, centered around the error column when the error line is too long
+        let error_line_len = lines.last().map_or(0, |line| line.chars().count());
+        let window_start = match error_line_len > MAX_CONTEXT_LINE_WIDTH {
+            true => position
+                .column
+                .saturating_sub(MAX_CONTEXT_LINE_WIDTH / 2)
+                .min(error_line_len - MAX_CONTEXT_LINE_WIDTH),
+            false => 0,
+        };
+
+        let lines = lines
+            .into_iter()
             .enumerate()
             .map(|(i, v)| {
                 format!(
                     "{:line$} | {}",
                     line_number - (show_lines - i - 1),
-                    v,
+                    truncate_line(v, window_start),
                     line = line_number_width
                 )
             })
             .collect::<Vec<String>>()
             .join("\n");
 
-        let padding = (0..position.column + line_number_width + 1)
+        // the error column within the rendered window
+        let column = match window_start {
+            0 => position.column,
+            start => position.column - start + ELLIPSIS.len(),
+        };
+
+        let padding = (0..column + line_number_width + 1)
             .map(|_| ' ')
             .collect::<String>();
 
@@ -124,3 +246,71 @@ impl ParseErrorWithPosition {
         ))
     }
 }
+
+/// Returns the window of [MAX_CONTEXT_LINE_WIDTH] chars of the line starting at the given index,
+/// with a marker on each side where the line is truncated
+fn truncate_line(line: &str, start: usize) -> Cow<'_, str> {
+    let len = line.chars().count();
+    if start == 0 && len <= MAX_CONTEXT_LINE_WIDTH {
+        return Cow::Borrowed(line);
+    }
+
+    let mut truncated = String::new();
+    if start > 0 {
+        truncated.push_str(ELLIPSIS);
+    }
+
+    truncated.extend(line.chars().skip(start).take(MAX_CONTEXT_LINE_WIDTH));
+    if start + MAX_CONTEXT_LINE_WIDTH < len {
+        truncated.push_str(ELLIPSIS);
+    }
+
+    Cow::Owned(truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseError, ParseErrorWithPosition, ParseFileError, MAX_CONTEXT_LINE_WIDTH};
+    use crate::position::Position;
+    use std::path::PathBuf;
+
+    #[test]
+    fn it_should_truncate_long_context_lines() {
+        let fields = (1..100)
+            .map(|i| format!("int32 f{} = {};", i, i))
+            .collect::<String>();
+        let content = format!(
+            "package pb;\nmessage Foo {{ {0}int32 = 100; {0} }}\n",
+            fields
+        );
+
+        let error_line = content.lines().nth(1).unwrap();
+        let column = error_line.find("= 100").unwrap() + 1;
+        let position = Position {
+            line: 2,
+            column,
+            offset: 0,
+        };
+
+        let error = ParseErrorWithPosition(ParseError::EOF, position)
+            .into_file_error(PathBuf::from("test.proto"), &content);
+        let message = match error {
+            ParseFileError::ParseError(message) => message,
+            error => panic!("unexpected error {:?}", error),
+        };
+
+        let lines = message.lines().collect::<Vec<_>>();
+        // the context lines are rendered with the same window, to keep them aligned with the error line
+        assert_eq!(lines[1], "1 | ...");
+
+        // the error line is truncated on both sides
+        let rendered = lines[2].strip_prefix("2 | ").unwrap();
+        assert!(rendered.starts_with("...") && rendered.ends_with("..."));
+        assert_eq!(rendered.len(), MAX_CONTEXT_LINE_WIDTH + 6);
+
+        // the error message is aligned with the error column, like untruncated lines
+        let padding = lines[3].len() - lines[3].trim_start().len();
+        assert_eq!(lines[3].trim_start(), "Unexpected end of file");
+        assert!(lines[2][padding + 1..].starts_with("= 100"), "{}", message);
+    }
+}
@@ -1,9 +1,21 @@
 use crate::{
-    file_parser::FileParser, import::Import, namespace::Namespace, parse_error::ParseFileError,
+    cache::{content_hash, Cache, CachedFile, ParseOptions, CACHE_VERSION},
+    field::Field,
+    file_parser::FileParser,
+    import::Import,
+    iter_ext::IterExt,
+    lint::{lint_message, LintConfig, LintKind, LintReport},
+    message::resolve_field_type,
+    namespace::Namespace,
+    parse_error::{ParseFileError, ResolveError},
+    r#type::Type,
+    well_known_types::well_known_file,
 };
+use linked_hash_map::LinkedHashMap;
 use std::{
     collections::HashMap,
-    path::{Path, PathBuf},
+    fmt::Write,
+    path::{Component, Path, PathBuf},
     rc::Rc,
 };
 
@@ -42,6 +54,45 @@ pub struct Parser {
 
     /// List of parsed files
     pub parsed_files: HashMap<Rc<Path>, Namespace>,
+
+    /// When set, files are parsed in strict mode. See [FileParser::set_strict]
+    strict: bool,
+
+    /// When set, required fields are rejected. See [FileParser::set_forbid_required]
+    forbid_required: bool,
+
+    /// When set, floating comments are collected. See [FileParser::set_collect_floating_comments]
+    collect_floating_comments: bool,
+
+    /// Total size in bytes of the parsed files content
+    total_bytes: usize,
+
+    /// When set, files larger than this size in bytes are rejected
+    max_file_size: Option<usize>,
+
+    /// When set, namespaces without types or services are kept in the root namespace
+    emit_empty_namespaces: bool,
+
+    /// When set, files are read from this map of path => content instead of the disk
+    virtual_fs: Option<HashMap<PathBuf, String>>,
+
+    /// The cached files loaded with [Parser::load_cache]
+    cache: HashMap<PathBuf, CachedFile>,
+
+    /// The options the cached files were parsed with
+    cache_options: ParseOptions,
+
+    /// List of (from, to) package aliases applied when building the root namespace
+    package_aliases: Vec<(String, String)>,
+
+    /// The content hash of each parsed file
+    content_hashes: HashMap<Rc<Path>, u64>,
+
+    /// Number of files loaded from the cache instead of being parsed
+    cache_hits: usize,
+
+    /// When set, the well-known types files are used when they are not found. See [Parser::register_builtin_types]
+    builtin_types: bool,
 }
 
 impl Parser {
@@ -50,9 +101,67 @@ impl Parser {
         Self {
             root_dir: root_dir.into(),
             parsed_files: HashMap::new(),
+            strict: false,
+            forbid_required: false,
+            collect_floating_comments: false,
+            total_bytes: 0,
+            max_file_size: None,
+            emit_empty_namespaces: false,
+            virtual_fs: None,
+            cache: HashMap::new(),
+            cache_options: ParseOptions::default(),
+            package_aliases: Vec::new(),
+            content_hashes: HashMap::new(),
+            cache_hits: 0,
+            builtin_types: false,
+        }
+    }
+
+    /// Returns a new parser that reads files from the given map of path => content.
+    /// Imports are resolved against the map, relative to its root
+    pub fn with_virtual_fs(files: HashMap<PathBuf, String>) -> Self {
+        Self {
+            virtual_fs: Some(files),
+            ..Self::new(PathBuf::new())
         }
     }
 
+    /// Enable or disable strict mode.
+    /// In strict mode, statements that are usually relaxed (e.g extra ";") are reported as errors
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Register the definitions of the well-known types (e.g google/protobuf/timestamp.proto),
+    /// so that imports of these files are resolved when they are not found in the root directory.
+    /// Files found in the root directory take precedence over the registered definitions
+    pub fn register_builtin_types(&mut self) {
+        self.builtin_types = true;
+    }
+
+    /// Enable or disable the rejection of proto2 `required` fields, see [FileParser::set_forbid_required]
+    pub fn set_forbid_required(&mut self, forbid_required: bool) {
+        self.forbid_required = forbid_required;
+    }
+
+    /// Enable or disable the collection of comments that are not attached to any definition into their message.
+    /// See [FileParser::set_collect_floating_comments]
+    pub fn set_collect_floating_comments(&mut self, enable: bool) {
+        self.collect_floating_comments = enable;
+    }
+
+    /// Set the maximum size in bytes of a parsed file.
+    /// Larger files are rejected with [ParseFileError::FileTooLarge]
+    pub fn set_max_file_size(&mut self, max_file_size: Option<usize>) {
+        self.max_file_size = max_file_size;
+    }
+
+    /// Set whether namespaces that don't define any type or service (e.g from ignored files or package-only files)
+    /// are kept when building the root namespace. They are pruned by default
+    pub fn set_emit_empty_namespaces(&mut self, emit_empty_namespaces: bool) {
+        self.emit_empty_namespaces = emit_empty_namespaces;
+    }
+
     pub fn ignore_files(&mut self, files: &[&str]) {
         for file in files {
             let path = PathBuf::from(file);
@@ -61,6 +170,39 @@ impl Parser {
         }
     }
 
+    /// Read the content of the given file, from the virtual file system if set or from the disk.
+    /// Files on disk larger than the max file size are rejected before being read
+    fn read_file(&self, path: &Path) -> Result<String, ParseFileError> {
+        let bytes = match &self.virtual_fs {
+            None => {
+                if let Some(max) = self.max_file_size {
+                    let size = std::fs::metadata(path)
+                        .map_err(|error| ParseFileError::Read(path.to_path_buf(), error))?
+                        .len() as usize;
+
+                    if size > max {
+                        let path = path.to_path_buf();
+                        return Err(ParseFileError::FileTooLarge { path, size });
+                    }
+                }
+
+                std::fs::read(path)
+            }
+            Some(files) => {
+                return files.get(path).cloned().ok_or_else(|| {
+                    let error = std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
+                    ParseFileError::Read(path.to_path_buf(), error)
+                })
+            }
+        };
+
+        let bytes = bytes.map_err(|error| ParseFileError::Read(path.to_path_buf(), error))?;
+        String::from_utf8(bytes).map_err(|error| ParseFileError::InvalidUtf8 {
+            path: path.to_path_buf(),
+            byte_offset: error.utf8_error().valid_up_to(),
+        })
+    }
+
     /// Parse the given file, and it's import dependencies
     /// The result will be merged into the root namespace of the parser
     pub fn parse_file<T: Into<Rc<Path>>>(&mut self, file_path: T) -> Result<(), ParseFileError> {
@@ -70,22 +212,98 @@ impl Parser {
             return Ok(());
         }
 
-        let path = self.root_dir.join(file_path.as_ref());
-        let content = match std::fs::read_to_string(&path) {
-            Ok(r) => r,
-            Err(error) => return Err(ParseFileError::Read(path, error)),
+        let path = self.full_path(&file_path);
+        let content = match self.read_file(&path) {
+            // fallback to the registered well-known types definitions
+            Err(ParseFileError::Read(read_path, error))
+                if self.builtin_types && error.kind() == std::io::ErrorKind::NotFound =>
+            {
+                match file_path.to_str().and_then(well_known_file) {
+                    Some(content) => content.to_string(),
+                    None => return Err(ParseFileError::Read(read_path, error)),
+                }
+            }
+            content => content?,
         };
 
-        // create the parser
-        let file_parser = FileParser::new(file_path.clone(), content.chars());
+        self.parse_content(file_path, content)
+    }
 
-        // parse the namespace
-        let ns = file_parser
-            .parse()
-            .map_err(|error| error.into_file_error(path, content.as_str()))?;
+    /// Parse the given proto content, registered under the given virtual file name, and it's import dependencies.
+    /// Files parsed afterwards can import it using this name.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use prosecco::parser::Parser;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut parser = Parser::new("protos");
+    /// parser.parse_str(Path::new("pb/hello.proto"), "package pb; message Hello {}")?;
+    /// parser.parse_str(
+    ///     Path::new("pb/world.proto"),
+    ///     r#"package pb; import "pb/hello.proto"; message World { Hello hello = 1; }"#,
+    /// )?;
+    ///
+    /// let root = parser.build_root()?;
+    /// assert!(root.lookup_message("pb.World").is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_str<T: Into<Rc<Path>>>(
+        &mut self,
+        name: T,
+        content: &str,
+    ) -> Result<(), ParseFileError> {
+        self.parse_content(name.into(), content.to_string())
+    }
+
+    /// Parse the given file content, and it's import dependencies
+    fn parse_content(
+        &mut self,
+        file_path: Rc<Path>,
+        content: String,
+    ) -> Result<(), ParseFileError> {
+        let path = self.full_path(&file_path);
+        if matches!(self.max_file_size, Some(max) if content.len() > max) {
+            let size = content.len();
+            return Err(ParseFileError::FileTooLarge { path, size });
+        }
+
+        self.total_bytes += content.len();
+
+        let hash = content_hash(&content);
+        self.content_hashes.insert(file_path.clone(), hash);
+
+        let ns = match self.cache.remove(file_path.as_ref()) {
+            // skip parsing files that did not change since the cache was saved
+            Some(cached) if cached.hash == hash && self.cache_options == self.parse_options() => {
+                self.cache_hits += 1;
+                cached.namespace.into_namespace(&file_path)
+            }
+            _ => {
+                // create the parser
+                let mut file_parser = FileParser::new(file_path.clone(), content.chars());
+                file_parser.set_strict(self.strict);
+                file_parser.set_forbid_required(self.forbid_required);
+                file_parser.set_collect_floating_comments(self.collect_floating_comments);
+
+                // parse the namespace
+                file_parser
+                    .parse()
+                    .map_err(|error| error.into_file_error(path, content.as_str()))?
+            }
+        };
 
         // get the list of imported files and parse them
         for import in ns.imports.iter() {
+            if escapes_root(import.as_path()) {
+                return Err(ParseFileError::ImportEscapesRoot {
+                    path: self.full_path(&file_path),
+                    import: import.as_path().to_path_buf(),
+                });
+            }
+
             self.parse_file(import.as_path())?;
         }
 
@@ -93,24 +311,426 @@ impl Parser {
         Ok(())
     }
 
+    /// Returns the options the files are parsed with
+    fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            strict: self.strict,
+            forbid_required: self.forbid_required,
+            collect_floating_comments: self.collect_floating_comments,
+        }
+    }
+
+    /// Returns the path of the given file joined against the root directory
+    fn full_path(&self, file_path: &Path) -> PathBuf {
+        self.root_dir.join(file_path)
+    }
+
+    /// Returns the imports of the given namespace along with their absolute path, sorted by path.
+    /// Paths are canonicalized when the file exists on disk
+    pub fn resolved_imports(&self, ns: &Namespace) -> Vec<(Import, PathBuf)> {
+        let mut imports = ns
+            .imports
+            .iter()
+            .map(|import| {
+                let path = self.full_path(import.as_path());
+                let path = match self.virtual_fs {
+                    None => std::fs::canonicalize(&path).unwrap_or(path),
+                    Some(_) => path,
+                };
+                (import.clone(), path)
+            })
+            .collect::<Vec<_>>();
+
+        imports.sort_by(|(_, a), (_, b)| a.cmp(b));
+        imports
+    }
+
+    /// Emit the `from` package (e.g pb.old) and its sub packages under the `to` package (e.g pb.new)
+    /// when building the root namespace. Type references are rewritten accordingly
+    pub fn add_package_alias(&mut self, from: &str, to: &str) {
+        self.package_aliases
+            .push((from.to_string(), to.to_string()));
+    }
+
+    /// Load a cache file saved by a previous run with [Parser::save_cache].
+    /// Files whose content did not change are then loaded from the cache instead of being parsed.
+    /// A missing cache file is treated as an empty cache
+    pub fn load_cache<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ParseFileError> {
+        let path = path.as_ref();
+        let content = match std::fs::read(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(ParseFileError::Read(path.to_path_buf(), error)),
+        };
+
+        let cache: Cache = serde_json::from_slice(&content)
+            .map_err(|error| ParseFileError::InvalidCache(path.to_path_buf(), error))?;
+
+        // caches saved with another format are ignored, their files are parsed again
+        if cache.version == CACHE_VERSION {
+            self.cache = cache.files;
+            self.cache_options = cache.options;
+        }
+
+        Ok(())
+    }
+
+    /// Save the parsed files into the given cache file, so they can be loaded by [Parser::load_cache].
+    /// Note: this must be called before [Parser::build_root]
+    pub fn save_cache<P: AsRef<Path>>(&self, path: P) -> Result<(), ParseFileError> {
+        let path = path.as_ref();
+        let files = self
+            .parsed_files
+            .iter()
+            .filter_map(|(file_path, ns)| {
+                let hash = self.content_hashes.get(file_path)?;
+                Some((file_path.to_path_buf(), CachedFile::new(*hash, ns)))
+            })
+            .collect();
+
+        let content = serde_json::to_vec(&Cache {
+            version: CACHE_VERSION,
+            options: self.parse_options(),
+            files,
+        })
+        .map_err(|error| ParseFileError::InvalidCache(path.to_path_buf(), error))?;
+
+        std::fs::write(path, content)
+            .map_err(|error| ParseFileError::Write(path.to_path_buf(), error))
+    }
+
+    /// Returns the number of files loaded from the cache instead of being parsed
+    pub fn cache_hit_count(&self) -> usize {
+        self.cache_hits
+    }
+
+    /// Returns the number of parsed files
+    pub fn parsed_file_count(&self) -> usize {
+        self.parsed_files.len()
+    }
+
+    /// Returns the total size in bytes of the parsed content, including strings and virtual files
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Returns the number of messages, including nested messages, defined in the parsed files
+    pub fn message_count(&self) -> usize {
+        self.parsed_files
+            .values()
+            .map(|ns| count_types(&ns.types, &|t| t.as_message().is_some()))
+            .sum()
+    }
+
+    /// Returns the number of enums, including nested enums, defined in the parsed files
+    pub fn enum_count(&self) -> usize {
+        self.parsed_files
+            .values()
+            .map(|ns| count_types(&ns.types, &|t| t.as_message().is_none()))
+            .sum()
+    }
+
+    /// Returns the number of services defined in the parsed files
+    pub fn service_count(&self) -> usize {
+        self.parsed_files.values().map(|ns| ns.services.len()).sum()
+    }
+
+    /// Lint the parsed files and report the fields whose type can't be resolved through the file's imports,
+    /// but is defined in another parsed file. protoc would reject these files for a missing import.
+    /// Note: this must be called before [Parser::build_root]
+    pub fn check_missing_imports(&self) -> Vec<ParseFileError> {
+        let mut errors = Vec::new();
+
+        self.for_each_unresolved_field(&mut |path, field_name, field, defined_in| {
+            if let Some(other_path) = defined_in {
+                let error = ResolveError::MissingImportForType {
+                    type_name: field.type_name.borrow().to_string(),
+                    field: field_name.to_string(),
+                    file: other_path.to_path_buf(),
+                };
+
+                errors.push(error.into_parse_file_error(self.root_dir.join(path)));
+            }
+        });
+
+        errors
+    }
+
+    /// Run all the lint checks on the parsed files. See [Parser::lint_with]
+    pub fn lint(&self) -> LintReport {
+        self.lint_with(&LintConfig::default())
+    }
+
+    /// Run the lint checks enabled in the given config on the parsed files,
+    /// and return the diagnostics sorted by file and line.
+    /// Note: this must be called before [Parser::build_root]
+    pub fn lint_with(&self, config: &LintConfig) -> LintReport {
+        let mut report = LintReport::default();
+
+        for (path, ns) in self.parsed_files.iter() {
+            for (name, msg) in ns
+                .types
+                .iter()
+                .filter_map(|(n, t)| Some((n, t.as_message()?)))
+            {
+                lint_message(name, msg, config, path, &mut report);
+            }
+
+            if config.unused_imports {
+                self.lint_unused_imports(path, ns, &mut report);
+            }
+        }
+
+        if config.unresolved_types || config.missing_imports {
+            self.for_each_unresolved_field(&mut |path, field_name, field, defined_in| {
+                let type_name = field.type_name.borrow();
+                match defined_in {
+                    Some(other_path) if config.missing_imports => report.add(
+                        LintKind::MissingImport,
+                        path.to_path_buf(),
+                        field.md.line,
+                        format!(
+                            "type {} of field {} is defined in {}, which is not imported",
+                            type_name,
+                            field_name,
+                            other_path.display()
+                        ),
+                    ),
+                    None if config.unresolved_types => report.add(
+                        LintKind::UnresolvedType,
+                        path.to_path_buf(),
+                        field.md.line,
+                        format!(
+                            "type {} of field {} can't be resolved",
+                            type_name, field_name
+                        ),
+                    ),
+                    _ => {}
+                }
+            });
+        }
+
+        report
+            .diagnostics
+            .sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        report
+    }
+
+    /// Report the internal imports of the given file that don't define any type used by its fields or rpcs
+    fn lint_unused_imports(&self, path: &Path, ns: &Namespace, report: &mut LintReport) {
+        // the field and rpc types referenced in the file, along with their resolve path
+        let mut references = Vec::new();
+        for (name, msg) in ns
+            .types
+            .iter()
+            .filter_map(|(n, t)| Some((n, t.as_message()?)))
+        {
+            msg.for_each_field(
+                vec![(name.as_str(), &msg.nested)],
+                &mut |_, field, resolve_path| {
+                    references.push((field.type_name.borrow().to_string(), resolve_path.to_vec()));
+                },
+            );
+        }
+
+        for rpc in ns.services.values().flat_map(|s| s.methods.values()) {
+            references.push((rpc.request_type.borrow().to_string(), Vec::new()));
+            references.push((rpc.response_type.borrow().to_string(), Vec::new()));
+        }
+
+        for import in ns.imports.iter() {
+            let import_path = match import {
+                Import::Internal(import_path, _) => import_path,
+                Import::Public(..) => continue,
+            };
+
+            let imported = match self.parsed_files.get(import_path.as_path()) {
+                Some(imported) => imported,
+                None => continue,
+            };
+
+            let dependencies: Vec<_> = self
+                .get_transitive_dependencies(imported)
+                .into_iter()
+                .start_with(imported)
+                .start_with(ns)
+                .collect();
+
+            let is_used = references.iter().any(|(type_name, resolve_path)| {
+                resolve_field_type(type_name, &[ns], resolve_path).is_none()
+                    && resolve_field_type(type_name, &dependencies, resolve_path).is_some()
+            });
+
+            if !is_used {
+                report.add(
+                    LintKind::UnusedImport,
+                    path.to_path_buf(),
+                    import.line(),
+                    format!("import {} is not used", import_path.display()),
+                );
+            }
+        }
+    }
+
+    /// Call the given function for each field whose type can't be resolved through the file's imports,
+    /// along with the path of another parsed file that defines the type if any
+    fn for_each_unresolved_field<F>(&self, callback: &mut F)
+    where
+        F: FnMut(&Path, &str, &Field, Option<&Path>),
+    {
+        let mut files = self.parsed_files.iter().collect::<Vec<_>>();
+        files.sort_by_key(|(path, _)| *path);
+
+        for (path, ns) in files.iter() {
+            let dependencies: Vec<_> = self
+                .get_dependencies(ns)
+                .into_iter()
+                .start_with(*ns)
+                .collect();
+
+            for (name, msg) in ns
+                .types
+                .iter()
+                .filter_map(|(n, t)| Some((n, t.as_message()?)))
+            {
+                msg.for_each_field(
+                    vec![(name.as_str(), &msg.nested)],
+                    &mut |field_name, field, resolve_path| {
+                        let type_name = field.type_name.borrow();
+                        if resolve_field_type(&type_name, &dependencies, resolve_path).is_some() {
+                            return;
+                        }
+
+                        // look for another file that defines the type
+                        let defined_in = files.iter().find(|(other_path, other)| {
+                            other_path != path
+                                && resolve_field_type(&type_name, &[ns, other], resolve_path)
+                                    .is_some()
+                        });
+
+                        callback(
+                            path,
+                            field_name,
+                            field,
+                            defined_in.map(|(other_path, _)| other_path.as_ref()),
+                        );
+                    },
+                );
+            }
+        }
+    }
+
     /// Build the namespace graph by consuming all the parsed files
     pub fn build_root(self) -> Result<Namespace, ParseFileError> {
+        let (root, errors) = self.build_root_collect_errors();
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(root),
+        }
+    }
+
+    /// Build the namespace graph by consuming all the parsed files.
+    /// Unlike [Parser::build_root], resolution does not stop at the first failure:
+    /// unresolved types are left as-is, and all the errors are returned along with the root namespace
+    pub fn build_root_collect_errors(self) -> (Namespace, Vec<ParseFileError>) {
+        let mut errors = Vec::new();
+
+        // files are processed sorted by path, so that the errors and the merged namespaces are deterministic
+        let mut files = self.parsed_files.iter().collect::<Vec<_>>();
+        files.sort_by_key(|(path, _)| *path);
+
         // normalize all files
-        for (path, namespace) in self.parsed_files.iter() {
+        for (path, namespace) in files {
             let dependencies = self.get_dependencies(namespace);
 
-            namespace
-                .resolve_types(dependencies)
-                .map_err(|err| err.into_parse_file_error(self.root_dir.join(path.as_ref())))?;
+            errors.extend(
+                namespace
+                    .resolve_types_collect_errors(dependencies)
+                    .into_iter()
+                    .map(|err| err.into_parse_file_error(self.root_dir.join(path.as_ref()))),
+            );
         }
 
         // build the namespace tree
+        let mut files = self.parsed_files.into_iter().collect::<Vec<_>>();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
         let mut root = Namespace::default();
-        for child in self.parsed_files.into_values() {
+        for (_, mut child) in files {
+            for (from, to) in self.package_aliases.iter() {
+                child.apply_package_alias(from, to);
+            }
             root.append_child(child)
         }
 
-        Ok(root)
+        if !self.emit_empty_namespaces {
+            root.prune_empty();
+        }
+
+        (root, errors)
+    }
+
+    /// Returns the top-level types visible to the importers of the given file, with their fully qualified name:
+    /// the types the file declares, followed by the types re-exported through `import public` statements
+    pub fn exported_types(&self, file_path: &Path) -> Vec<(String, &Type)> {
+        let namespace = match self.parsed_files.get(file_path) {
+            Some(ns) => ns,
+            None => return Vec::new(),
+        };
+
+        self.get_transitive_dependencies(namespace)
+            .into_iter()
+            .start_with(namespace)
+            .flat_map(|ns| {
+                ns.types.iter().map(move |(name, t)| {
+                    let fqn = ns
+                        .path
+                        .iter()
+                        .chain(std::iter::once(name))
+                        .map(String::as_str)
+                        .collect::<Vec<_>>()
+                        .join(".");
+                    (fqn, t)
+                })
+            })
+            .collect()
+    }
+
+    /// Returns a [Graphviz DOT] graph of the parsed files, where nodes are files and edges are imports.
+    /// Internal imports are drawn with solid edges, public imports with dashed edges
+    ///
+    /// [Graphviz DOT]: https://graphviz.org/doc/info/lang.html
+    pub fn dependency_dot(&self) -> String {
+        let mut files = self.parsed_files.iter().collect::<Vec<_>>();
+        files.sort_by_key(|(path, _)| *path);
+
+        let mut output = String::from("digraph protos {\n");
+        for (path, ns) in files {
+            let path = path.to_string_lossy();
+            writeln!(output, "  {:?};", path).unwrap();
+
+            let mut imports = ns.imports.iter().collect::<Vec<_>>();
+            imports.sort_by_key(|import| import.as_path());
+
+            for import in imports {
+                let style = match import {
+                    Import::Public(..) => " [style=dashed]",
+                    Import::Internal(..) => "",
+                };
+
+                writeln!(
+                    output,
+                    "  {:?} -> {:?}{};",
+                    path,
+                    import.as_path().to_string_lossy(),
+                    style
+                )
+                .unwrap();
+            }
+        }
+
+        output.push_str("}\n");
+        output
     }
 
     fn get_dependencies(&self, namespace: &Namespace) -> Vec<&Namespace> {
@@ -131,61 +751,123 @@ impl Parser {
             .imports
             .iter()
             .flat_map(|f| match f {
-                Import::Public(path) => {
+                Import::Public(path, _) => {
                     let ns = &self.parsed_files[path.as_path()];
                     let mut vec = vec![ns];
                     vec.append(&mut self.get_transitive_dependencies(ns));
                     vec
                 }
-                Import::Internal(_) => Vec::new(),
+                Import::Internal(..) => Vec::new(),
             })
             .collect()
     }
 }
 
+/// Returns true if the given import path is absolute or traverses outside of the root directory (e.g ../foo.proto)
+fn escapes_root(path: &Path) -> bool {
+    let mut depth = 0usize;
+
+    for component in path.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return true,
+        }
+    }
+
+    false
+}
+
+/// Count the types and nested types matching the given predicate
+fn count_types(types: &LinkedHashMap<String, Type>, predicate: &impl Fn(&Type) -> bool) -> usize {
+    types
+        .values()
+        .map(|t| {
+            let nested = match t {
+                Type::Message(msg) => count_types(&msg.nested, predicate),
+                Type::Enum(_) => 0,
+            };
+
+            nested + predicate(t) as usize
+        })
+        .sum()
+}
+
 #[cfg(test)]
 pub mod test_util {
     use crate::{file_parser::FileParser, namespace::Namespace, parser::Parser};
     use std::{
+        collections::HashMap,
         path::{Path, PathBuf},
         rc::Rc,
     };
 
+    /// Returns the root namespace built from the given test.proto content
     pub fn parse_test_file(text: &'static str) -> Namespace {
-        let file_path: PathBuf = "test.proto".into();
-        let file_path: Rc<Path> = file_path.into();
-        let file_parser = FileParser::new(file_path.clone(), text.chars());
+        parse_test_files(&[("test.proto", text)])
+            .build_root()
+            .expect("create root namespace without errors")
+    }
 
-        let ns = file_parser
-            .parse()
-            .expect("parse test.proto without errors");
+    /// Returns a parser with the given (file name, content) files parsed.
+    /// Imports are not followed, each imported file must be part of the given files
+    pub fn parse_test_files(files: &[(&str, &str)]) -> Parser {
+        let mut parser = Parser::new(".");
 
-        let root_dir: PathBuf = ".".into();
-        let mut parser = Parser::new(root_dir);
-        parser.parsed_files.insert(file_path.into(), ns);
+        for (file_name, text) in files {
+            let file_path: Rc<Path> = Path::new(file_name).into();
+            let ns = FileParser::new(file_path.clone(), text.chars())
+                .parse()
+                .unwrap_or_else(|error| {
+                    panic!("parse {} without errors: {:?}", file_name, error.0)
+                });
+            parser.parsed_files.insert(file_path, ns);
+        }
 
         parser
-            .build_root()
-            .expect("create root namespace without errors")
+    }
+
+    /// Returns the files of a virtual fs from the given (file name, content) pairs, see [Parser::with_virtual_fs]
+    pub fn virtual_fs(files: &[(&str, &str)]) -> HashMap<PathBuf, String> {
+        files
+            .iter()
+            .map(|(file_name, text)| (PathBuf::from(file_name), text.to_string()))
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Parser;
+    use super::{
+        test_util::{parse_test_file, parse_test_files, virtual_fs},
+        Parser,
+    };
+    use crate::import::Import;
+    use crate::lint::{LintConfig, LintKind};
+    use crate::parse_error::ParseFileError;
+    use indoc::indoc;
     use pretty_assertions::assert_eq;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
+
+    /// Returns an empty temporary directory, unique to the given test and process
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("prosecco-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("it should create the test dir");
+        dir
+    }
 
     #[test]
     fn test_serialize_root() {
         let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("protos");
-        let expected_output = std::fs::read_to_string(&root_dir.join("descriptors.json"))
+        let expected_output = std::fs::read_to_string(root_dir.join("descriptors.json"))
             .expect("descriptors.json should exist");
 
         let mut parser = Parser::new(root_dir);
 
         parser
-            .parse_file(PathBuf::from("foo.proto").into())
+            .parse_file(PathBuf::from("foo.proto"))
             .expect("it should parse one.proto");
 
         let root = parser.build_root().expect("it should build root");
@@ -193,4 +875,590 @@ mod tests {
 
         assert_eq!(output, expected_output)
     }
+
+    #[test]
+    fn test_serialize_package_less_file() {
+        let root = parse_test_file(indoc! {r#"
+        message Foo {
+          Bar bar = 1;
+        }
+
+        message Bar {}
+        "#});
+
+        assert!(root.path.is_empty());
+        assert!(root.types.contains_key("Foo"));
+
+        let output = serde_json::to_string_pretty(&root).unwrap();
+        let expected_output = indoc! {r#"
+        {
+          "nested": {
+            "Foo": {
+              "fields": {
+                "bar": {
+                  "type": ".Bar",
+                  "id": 1
+                }
+              }
+            },
+            "Bar": {
+              "fields": {}
+            }
+          }
+        }"#};
+
+        assert_eq!(output, expected_output)
+    }
+
+    #[test]
+    fn test_build_root_collect_errors() {
+        let parser = parse_test_files(&[
+            (
+                "one.proto",
+                "package pb.one; message One { Missing1 a = 1; }",
+            ),
+            (
+                "two.proto",
+                "package pb.two; message Two { Missing2 b = 1; }",
+            ),
+        ]);
+
+        let (root, errors) = parser.build_root_collect_errors();
+        let mut errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        errors.sort();
+
+        assert_eq!(
+            errors,
+            vec![
+                "File ./one.proto, Failed to resolve field: Missing1 a",
+                "File ./two.proto, Failed to resolve field: Missing2 b",
+            ]
+        );
+
+        assert!(root.child("pb.one").is_some());
+        assert!(root.child("pb.two").is_some());
+    }
+
+    #[test]
+    fn test_dependency_dot() {
+        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("protos");
+        let mut parser = Parser::new(root_dir);
+
+        parser
+            .parse_file(PathBuf::from("foo.proto"))
+            .expect("it should parse foo.proto");
+
+        let output = parser.dependency_dot();
+        let expected_output = indoc! {r#"
+        digraph protos {
+          "bar.proto";
+          "foo.proto";
+          "foo.proto" -> "bar.proto";
+        }
+        "#};
+
+        assert_eq!(output, expected_output);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("protos");
+        let mut parser = Parser::new(root_dir.clone());
+
+        parser
+            .parse_file(PathBuf::from("foo.proto"))
+            .expect("it should parse foo.proto");
+
+        let total_bytes = ["foo.proto", "bar.proto"]
+            .iter()
+            .map(|f| std::fs::read_to_string(root_dir.join(f)).unwrap().len())
+            .sum::<usize>();
+
+        assert_eq!(parser.parsed_file_count(), 2);
+        assert_eq!(parser.total_bytes(), total_bytes);
+        assert_eq!(parser.message_count(), 9);
+        assert_eq!(parser.enum_count(), 2);
+        assert_eq!(parser.service_count(), 1);
+    }
+
+    #[test]
+    fn test_check_missing_imports() {
+        let parser = parse_test_files(&[
+            ("a.proto", "package pb.a; message A { pb.b.B b = 1; }"),
+            ("b.proto", "package pb.b; message B {}"),
+            (
+                "c.proto",
+                "package pb.c; import \"b.proto\"; message C { pb.b.B b = 1; }",
+            ),
+        ]);
+
+        let errors = parser
+            .check_missing_imports()
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec!["File ./a.proto, Missing import for type pb.b.B of field b, defined in b.proto"]
+        );
+    }
+
+    #[test]
+    fn test_virtual_fs() {
+        let files = virtual_fs(&[
+            (
+                "a.proto",
+                "package pb.a; import \"b/b.proto\"; message A { pb.b.B b = 1; }",
+            ),
+            ("b/b.proto", "package pb.b; message B {}"),
+        ]);
+
+        let mut parser = Parser::with_virtual_fs(files);
+        parser
+            .parse_file(PathBuf::from("a.proto"))
+            .expect("it should parse a.proto");
+
+        assert_eq!(parser.parsed_file_count(), 2);
+
+        let error = parser
+            .parse_file(PathBuf::from("missing.proto"))
+            .expect_err("it should fail to read a missing file");
+        assert!(error.to_string().contains("missing.proto"));
+
+        let root = parser.build_root().expect("it should build root");
+        let a = root.lookup_message("pb.a.A").expect("A should exist");
+        assert_eq!(*a.fields["b"].type_name.borrow(), ".pb.b.B");
+    }
+
+    #[test]
+    fn test_max_file_size() {
+        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("protos");
+        let size = std::fs::read_to_string(root_dir.join("bar.proto"))
+            .expect("bar.proto should exist")
+            .len();
+
+        let mut parser = Parser::new(root_dir.clone());
+        parser.set_max_file_size(Some(size));
+        parser
+            .parse_file(PathBuf::from("bar.proto"))
+            .expect("it should parse bar.proto");
+
+        let mut parser = Parser::new(root_dir.clone());
+        parser.set_max_file_size(Some(size - 1));
+        let error = parser
+            .parse_file(PathBuf::from("bar.proto"))
+            .expect_err("bar.proto should be too large");
+
+        assert!(matches!(
+            error,
+            ParseFileError::FileTooLarge { path, size: s } if path == root_dir.join("bar.proto") && s == size
+        ));
+
+        // in-memory content is checked before being parsed
+        let mut parser = Parser::new(root_dir);
+        parser.set_max_file_size(Some(4));
+        let error = parser
+            .parse_str(Path::new("foo.proto"), "package pb;")
+            .expect_err("the content should be too large");
+
+        assert!(matches!(
+            error,
+            ParseFileError::FileTooLarge { size: 11, .. }
+        ));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let root_dir = test_dir("invalid-utf8");
+        std::fs::write(root_dir.join("invalid.proto"), b"message Foo {}\n// \xff\n")
+            .expect("it should write the test file");
+
+        let mut parser = Parser::new(root_dir.clone());
+        let error = parser
+            .parse_file(PathBuf::from("invalid.proto"))
+            .expect_err("it should reject invalid utf-8");
+
+        assert!(matches!(
+            error,
+            ParseFileError::InvalidUtf8 { path, byte_offset: 18 } if path == root_dir.join("invalid.proto")
+        ));
+
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
+    #[test]
+    fn test_exported_types() {
+        let parser = parse_test_files(&[
+            (
+                "a.proto",
+                "package pb.a; import public \"b.proto\"; import \"c.proto\"; message A {}",
+            ),
+            ("b.proto", "package pb.b; message B {}"),
+            ("c.proto", "package pb.c; message C {}"),
+        ]);
+
+        let exported = parser
+            .exported_types(Path::new("a.proto"))
+            .into_iter()
+            .map(|(fqn, _)| fqn)
+            .collect::<Vec<_>>();
+
+        assert_eq!(exported, vec!["pb.a.A", "pb.b.B"]);
+    }
+
+    #[test]
+    fn test_import_escapes_root() {
+        for import in ["../../etc/passwd", "foo/../../bar.proto", "/etc/passwd"] {
+            let a = format!("package pb.a; import \"{}\";", import);
+            let files = virtual_fs(&[("a.proto", &a), ("bar.proto", "package pb.bar;")]);
+
+            let mut parser = Parser::with_virtual_fs(files);
+            let error = parser
+                .parse_file(PathBuf::from("a.proto"))
+                .expect_err("it should reject imports outside of the root");
+
+            assert!(
+                matches!(error, ParseFileError::ImportEscapesRoot { import: ref path, .. } if path == Path::new(import)),
+                "{}",
+                error
+            );
+        }
+
+        let files = virtual_fs(&[
+            ("a.proto", "package pb.a; import \"foo/../bar.proto\";"),
+            ("bar.proto", "package pb.bar;"),
+        ]);
+
+        // foo/../bar.proto stays inside the root, it fails to load since it's not a key of the virtual fs
+        let error = Parser::with_virtual_fs(files)
+            .parse_file(PathBuf::from("a.proto"))
+            .expect_err("foo/../bar.proto is not in the virtual fs");
+        assert!(matches!(error, ParseFileError::Read(..)), "{}", error);
+    }
+
+    #[test]
+    fn test_prune_empty_namespaces() {
+        let build = |emit_empty_namespaces: bool| {
+            let mut parser = parse_test_files(&[
+                ("a.proto", "package pb.a; message A {}"),
+                ("empty.proto", "package pb.empty.inner;"),
+            ]);
+            parser.ignore_files(&["ignored.proto"]);
+            parser.set_emit_empty_namespaces(emit_empty_namespaces);
+
+            let root = parser.build_root().expect("it should build root");
+            serde_json::to_string(&root).unwrap()
+        };
+
+        assert_eq!(
+            build(false),
+            r#"{"nested":{"pb":{"nested":{"a":{"nested":{"A":{"fields":{}}}}}}}}"#
+        );
+        assert_eq!(
+            build(true),
+            r#"{"nested":{"pb":{"nested":{"a":{"nested":{"A":{"fields":{}}}},"empty":{"nested":{"inner":{"nested":{}}}}}}}}"#
+        );
+    }
+
+    #[test]
+    fn test_lint() {
+        let parser = parse_test_files(&[
+            (
+                "a.proto",
+                indoc! {r#"
+                package pb.a;
+                import "b.proto";
+                import "c.proto";
+
+                message A {
+                  pb.b.B b = 1;
+                  int32 foo_bar = 2;
+                  int32 fooBar = 3;
+                  int32 baz = 3;
+                }
+                "#},
+            ),
+            ("b.proto", "package pb.b; message B {}"),
+            ("c.proto", "package pb.c; message C {}"),
+        ]);
+
+        let report = parser.lint();
+        let diagnostics = report
+            .diagnostics
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                "a.proto:3 [unused-import] import c.proto is not used",
+                "a.proto:8 [json-name-collision] fields foo_bar and fooBar of A have the same JSON name fooBar",
+                "a.proto:9 [duplicate-field-id] fields fooBar and baz of A use the same number 3",
+            ]
+        );
+
+        let config = LintConfig {
+            unused_imports: false,
+            json_name_collisions: false,
+            ..Default::default()
+        };
+        let report = parser.lint_with(&config);
+        assert_eq!(report.of_kind(LintKind::DuplicateFieldId).count(), 1);
+        assert_eq!(report.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_resolved_imports() {
+        let root_dir = test_dir("resolved-imports");
+        std::fs::create_dir_all(root_dir.join("sub")).unwrap();
+        for (file_name, text) in [
+            (
+                "a.proto",
+                "import \"sub/b.proto\"; import public \"sub/../c.proto\";",
+            ),
+            ("sub/b.proto", "package pb.b;"),
+            ("c.proto", "package pb.c;"),
+        ] {
+            std::fs::write(root_dir.join(file_name), text).unwrap();
+        }
+
+        let mut parser = Parser::new(root_dir.clone());
+        parser.parse_file(Path::new("a.proto")).unwrap();
+
+        let ns = &parser.parsed_files[Path::new("a.proto")];
+        let root_dir = std::fs::canonicalize(root_dir).unwrap();
+
+        assert_eq!(
+            parser.resolved_imports(ns),
+            vec![
+                (
+                    Import::Public(PathBuf::from("sub/../c.proto"), 1),
+                    root_dir.join("c.proto")
+                ),
+                (
+                    Import::Internal(PathBuf::from("sub/b.proto"), 1),
+                    root_dir.join("sub/b.proto")
+                ),
+            ]
+        );
+
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache_dir = test_dir("cache");
+        let cache_path = cache_dir.join("cache.json");
+        let files = |hello: &str| {
+            virtual_fs(&[
+                (
+                    "a.proto",
+                    indoc! {r#"
+                package pb.a;
+                import "b.proto";
+
+                service Greeter {
+                  // Say hello
+                  rpc SayHello (pb.b.Hello) returns (pb.b.Hello) {
+                    option (pgm.http.rule) = { GET: "/hello" };
+                  }
+                }
+                "#},
+                ),
+                ("b.proto", hello),
+            ])
+        };
+
+        let hello = "package pb.b; message Hello { oneof kind { string name = 1; } }";
+        let mut parser = Parser::with_virtual_fs(files(hello));
+        parser.load_cache(&cache_path).unwrap();
+        parser.parse_file(Path::new("a.proto")).unwrap();
+        parser.save_cache(&cache_path).unwrap();
+        let expected = serde_json::to_value(parser.build_root().unwrap()).unwrap();
+
+        // unchanged files are loaded from the cache
+        let mut parser = Parser::with_virtual_fs(files(hello));
+        parser.load_cache(&cache_path).unwrap();
+        parser.parse_file(Path::new("a.proto")).unwrap();
+        assert_eq!(parser.cache_hit_count(), 2);
+
+        let ns = &parser.parsed_files[Path::new("a.proto")];
+        let rpc = &ns.services["Greeter"].methods["SayHello"];
+        assert_eq!(rpc.md.comment_text(), Some(" Say hello"));
+        assert_eq!(rpc.md.file_path.as_ref(), Path::new("a.proto"));
+
+        let root = parser.build_root().unwrap();
+        assert_eq!(serde_json::to_value(root).unwrap(), expected);
+
+        // changed files are parsed again
+        let mut parser = Parser::with_virtual_fs(files("package pb.b; message Hello {}"));
+        parser.load_cache(&cache_path).unwrap();
+        parser.parse_file(Path::new("a.proto")).unwrap();
+        assert_eq!(parser.cache_hit_count(), 1);
+
+        // caches saved with other parser options are ignored
+        let mut parser = Parser::with_virtual_fs(files(hello));
+        parser.set_forbid_required(true);
+        parser.load_cache(&cache_path).unwrap();
+        parser.parse_file(Path::new("a.proto")).unwrap();
+        assert_eq!(parser.cache_hit_count(), 0);
+
+        // caches saved with another format are ignored
+        let mut cache: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&cache_path).unwrap()).unwrap();
+        cache.as_object_mut().unwrap().remove("version");
+        std::fs::write(&cache_path, cache.to_string()).unwrap();
+
+        let mut parser = Parser::with_virtual_fs(files(hello));
+        parser.load_cache(&cache_path).unwrap();
+        parser.parse_file(Path::new("a.proto")).unwrap();
+        assert_eq!(parser.cache_hit_count(), 0);
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_default_package_type() {
+        let files = virtual_fs(&[
+            (
+                "a.proto",
+                indoc! {r#"
+                package pb;
+                import "b.proto";
+
+                message A {
+                  RootType root = 1;
+                  .RootType absolute_root = 2;
+                }
+                "#},
+            ),
+            ("b.proto", "message RootType {}"),
+        ]);
+
+        let mut parser = Parser::with_virtual_fs(files);
+        parser.parse_file(Path::new("a.proto")).unwrap();
+        let root = parser.build_root().expect("it should resolve RootType");
+
+        let a = root.lookup_message("pb.A").unwrap();
+        assert_eq!(*a.fields["root"].type_name.borrow(), ".RootType");
+        assert_eq!(*a.fields["absolute_root"].type_name.borrow(), ".RootType");
+    }
+
+    #[test]
+    fn test_package_alias() {
+        let files = virtual_fs(&[
+            (
+                "a.proto",
+                indoc! {r#"
+                package pb.app;
+                import "old.proto";
+                import "old_v1.proto";
+
+                message A {
+                  pb.old.Foo foo = 1;
+                  pb.old.v1.Bar bar = 2;
+                }
+
+                service Greeter {
+                  rpc Greet (pb.old.Foo) returns (A) {}
+                }
+                "#},
+            ),
+            (
+                "old.proto",
+                "package pb.old; import \"old_v1.proto\"; message Foo { Foo.Inner inner = 1; message Inner {} }",
+            ),
+            ("old_v1.proto", "package pb.old.v1; message Bar {}"),
+        ]);
+
+        let mut parser = Parser::with_virtual_fs(files);
+        parser.add_package_alias("pb.old", "pb.new");
+        parser.parse_file(Path::new("a.proto")).unwrap();
+        let root = parser.build_root().unwrap();
+
+        assert!(root.child("pb.old").is_none());
+        assert!(root.lookup_message("pb.new.Foo").is_some());
+        assert!(root.lookup_message("pb.new.v1.Bar").is_some());
+
+        let type_name = |msg: &str, field: &str| {
+            root.lookup_message(msg).unwrap().fields[field]
+                .type_name
+                .borrow()
+                .to_string()
+        };
+        assert_eq!(type_name("pb.app.A", "foo"), ".pb.new.Foo");
+        assert_eq!(type_name("pb.app.A", "bar"), ".pb.new.v1.Bar");
+        assert_eq!(type_name("pb.new.Foo", "inner"), ".pb.new.Foo.Inner");
+
+        let rpc = root.find_rpc("pb.app.Greeter", "Greet").unwrap();
+        assert_eq!(*rpc.request_type.borrow(), ".pb.new.Foo");
+        assert_eq!(*rpc.response_type.borrow(), ".pb.app.A");
+    }
+
+    #[test]
+    fn test_split_package_merge_order() {
+        let build = || {
+            let files = virtual_fs(&[
+                ("a.proto", "package pb.foo; message A {}"),
+                ("b.proto", "package pb.foo; message B {}"),
+                ("c.proto", "package pb.foo; message C {}"),
+                ("d.proto", "package pb.foo; message D {}"),
+            ]);
+
+            let mut parser = Parser::with_virtual_fs(files);
+            for file_name in ["d.proto", "b.proto", "a.proto", "c.proto"] {
+                parser.parse_file(Path::new(file_name)).unwrap();
+            }
+
+            parser.build_root().unwrap()
+        };
+
+        for _ in 0..10 {
+            let root = build();
+            let foo = root.child("pb.foo").unwrap();
+            assert_eq!(
+                foo.types.keys().collect::<Vec<_>>(),
+                vec!["A", "B", "C", "D"]
+            );
+        }
+    }
+
+    #[test]
+    fn test_builtin_types() {
+        let files = virtual_fs(&[(
+            "event.proto",
+            indoc! {r#"
+            syntax = "proto3";
+            package pb.events;
+
+            import "google/protobuf/timestamp.proto";
+
+            message Event {
+              google.protobuf.Timestamp created_at = 1;
+            }
+            "#},
+        )]);
+
+        let mut parser = Parser::with_virtual_fs(files.clone());
+        let error = parser
+            .parse_file(PathBuf::from("event.proto"))
+            .expect_err("timestamp.proto is not defined");
+        assert!(error.to_string().contains("timestamp.proto"), "{}", error);
+
+        let mut parser = Parser::with_virtual_fs(files);
+        parser.register_builtin_types();
+        parser
+            .parse_file(PathBuf::from("event.proto"))
+            .expect("it should use the builtin timestamp.proto");
+
+        let root = parser.build_root().expect("it should build root");
+        let event = root.lookup_message("pb.events.Event").unwrap();
+        assert_eq!(
+            *event.fields["created_at"].type_name.borrow(),
+            ".google.protobuf.Timestamp"
+        );
+        assert!(root.lookup_message("google.protobuf.Timestamp").is_some());
+    }
 }
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// Reserved represents a proto [reserved] field range or field name
+/// Ranges are inclusive and serialized as [start, end] to match the protobuf.js format
+///
+/// [reserved]: https://developers.google.com/protocol-buffers/docs/proto3#reserved
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Reserved {
+    Range(i32, i32),
+    Name(String),
+}
+
+impl Reserved {
+    /// Returns true if the given id is reserved
+    pub fn contains_id(&self, id: i32) -> bool {
+        match self {
+            Self::Range(start, end) => (*start..=*end).contains(&id),
+            Self::Name(_) => false,
+        }
+    }
+
+    /// Returns true if the given name is reserved
+    pub fn contains_name(&self, name: &str) -> bool {
+        match self {
+            Self::Range(..) => false,
+            Self::Name(reserved) => reserved == name,
+        }
+    }
+}
@@ -1,3 +1,17 @@
+//! Proto [scalar value types]
+//!
+//! # Example:
+//!
+//! ```
+//! use prosecco::scalar;
+//!
+//! assert!(scalar::is_scalar("int64"));
+//! assert!(!scalar::is_scalar(".pb.hello.SayHelloRequest"));
+//! assert_eq!(scalar::all().count(), 15);
+//! ```
+//!
+//! [scalar value types]: https://developers.google.com/protocol-buffers/docs/overview#scalar
+
 use phf::phf_set;
 
 /// scalars defines all the possible [scalar value types]
@@ -8,3 +22,13 @@ pub static SCALARS: phf::Set<&'static str> = phf_set! {
     "fixed32", "fixed64", "sfixed32", "sfixed64",
     "bool", "string", "bytes"
 };
+
+/// Returns true if the given type name is a scalar type
+pub fn is_scalar(type_name: &str) -> bool {
+    SCALARS.contains(type_name)
+}
+
+/// Returns an iterator over all the scalar type names
+pub fn all() -> impl Iterator<Item = &'static str> {
+    SCALARS.iter().copied()
+}
@@ -2,7 +2,7 @@ use linked_hash_map::LinkedHashMap;
 use serde::Serialize;
 use std::cell::RefCell;
 
-use crate::metadata::Metadata;
+use crate::{http_options::HTTPOptions, metadata::Metadata};
 
 /// utility function used by serde skip_serializing_if directive
 /// is_false is used to remove false boolean from the serialized output
@@ -37,6 +37,32 @@ impl Service {
     }
 }
 
+/// The [idempotency level] of a rpc method, used to decide whether a request can be retried
+///
+/// [idempotency level]: https://github.com/protocolbuffers/protobuf/blob/master/src/google/protobuf/descriptor.proto
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum IdempotencyLevel {
+    #[default]
+    Unknown,
+
+    /// The method has no side effects (e.g a GET)
+    NoSideEffects,
+
+    /// The method can be safely retried (e.g a PUT or DELETE)
+    Idempotent,
+}
+
+impl IdempotencyLevel {
+    /// Returns the idempotency level defined by the `idempotency_level` option
+    fn from_metadata(md: &Metadata) -> Self {
+        match md.option_value("idempotency_level") {
+            Some("NO_SIDE_EFFECTS") => Self::NoSideEffects,
+            Some("IDEMPOTENT") => Self::Idempotent,
+            _ => Self::Unknown,
+        }
+    }
+}
+
 /// Rpc defines a [rpc] method of a Service
 /// [rpc]: https://developers.google.com/protocol-buffers/docs/proto3#services
 #[derive(Debug, Serialize)]
@@ -56,6 +82,10 @@ pub struct Rpc {
     #[serde(skip_serializing_if = "is_false")]
     pub response_stream: bool,
 
+    /// The idempotency level set with the `idempotency_level` option
+    #[serde(skip_serializing)]
+    pub idempotency_level: IdempotencyLevel,
+
     /// metadata associated to the Enum
     #[serde(skip_serializing)]
     pub md: Metadata,
@@ -75,7 +105,13 @@ impl Rpc {
             request_stream,
             response_type: RefCell::new(response_type),
             response_stream,
+            idempotency_level: IdempotencyLevel::from_metadata(&md),
             md,
         }
     }
+
+    /// Returns the (code, type) tuples of the HTTP errors returned by this rpc, or None for non HTTP rpcs
+    pub fn http_error_map(&self) -> Option<Vec<(String, String)>> {
+        HTTPOptions::from(&self.md.options).map(|options| options.error_map())
+    }
 }
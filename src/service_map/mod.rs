@@ -29,7 +29,11 @@
 //! }
 //!```
 
-use crate::{http_options::HTTPOptions, namespace::Namespace, service::Rpc};
+use crate::{
+    http_options::{HTTPBinding, HTTPOptions},
+    namespace::Namespace,
+    service::Rpc,
+};
 use serde::{Serialize, Serializer};
 use std::{borrow::Cow, cell::Cell, collections::BTreeMap, vec};
 
@@ -82,60 +86,170 @@ impl<'a> ServiceMapNode<'a> {
     }
 }
 
-/// Create the service tree map with the given namespace
+/// How the dynamic segments of the urls (e.g `:name`) are keyed in the service tree map
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PathStyle {
+    /// Dynamic segments are replaced by "*"
+    #[default]
+    Wildcard,
+
+    /// Dynamic segments are kept as is (e.g ":name")
+    Params,
+}
+
+/// Configure the service tree map generated by [create_with]
+#[derive(Debug, Clone)]
+pub struct ServiceMapConfig {
+    /// How the dynamic segments of the urls are keyed
+    pub path_style: PathStyle,
+
+    /// Whether rpcs with a streamed request or response are included
+    pub include_streaming: bool,
+}
+
+impl Default for ServiceMapConfig {
+    fn default() -> Self {
+        Self {
+            path_style: PathStyle::default(),
+            include_streaming: true,
+        }
+    }
+}
+
+/// Create the service tree map with the given namespace, using the default config
 pub fn create(ns: &Namespace) -> ServiceTreeMap<'_> {
+    create_with(ns, &ServiceMapConfig::default())
+}
+
+/// Create the service tree map with the given namespace and config
+pub fn create_with<'a>(ns: &'a Namespace, config: &ServiceMapConfig) -> ServiceTreeMap<'a> {
     let map = Cell::new(BTreeMap::new());
-    populate(&map, &ns);
+    populate(&map, ns, config);
     map.take()
 }
 
+/// Returns the (method type, url) routes of the given rpc.
+/// The method type is the lowercase http method of each http binding, or "grpc" when the rpc has no http options
+fn rpc_routes<'a>(ns: &Namespace, name: &str, rpc: &'a Rpc) -> Vec<(Cow<'a, str>, Cow<'a, str>)> {
+    match HTTPOptions::from(&rpc.md.options) {
+        Some(HTTPOptions { bindings, .. }) => bindings
+            .into_iter()
+            .map(|HTTPBinding { method, path, .. }| (Cow::from(method.to_lowercase()), path))
+            .collect(),
+        None => vec![(Cow::from("grpc"), Cow::from(ns.grpc_path(name)))],
+    }
+}
+
+/// A route of the flat service map, see [create_flat]
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FlatRoute<'a> {
+    /// The fully qualified name of the service
+    pub service: String,
+
+    /// The name of the rpc
+    pub method: &'a str,
+
+    /// The lowercase http method, or "grpc" when the rpc has no http options
+    pub http_method: Cow<'a, str>,
+
+    /// The url of the route
+    pub path: Cow<'a, str>,
+
+    /// The request type name
+    pub request: String,
+
+    /// The response type name
+    pub response: String,
+
+    /// Whether the request or the response is streamed
+    pub streaming: bool,
+}
+
+/// Create the flat list of routes with the given namespace.
+/// This is an alternative to the service tree map, for gateways that expect a list of routes
+pub fn create_flat(ns: &Namespace) -> Vec<FlatRoute<'_>> {
+    let mut routes = Vec::new();
+    populate_flat(&mut routes, ns);
+    routes
+}
+
+/// Recursively populate the flat list of routes with the given namespace
+fn populate_flat<'a>(routes: &mut Vec<FlatRoute<'a>>, ns: &'a Namespace) {
+    for (service_name, service) in ns.services.iter() {
+        let service_name = ns
+            .path
+            .iter()
+            .map(|s| s.as_str())
+            .chain(std::iter::once(service_name.as_str()))
+            .collect::<Vec<_>>()
+            .join(".");
+
+        for (name, rpc) in service.methods.iter() {
+            for (http_method, path) in rpc_routes(ns, name, rpc) {
+                routes.push(FlatRoute {
+                    service: service_name.clone(),
+                    method: name,
+                    http_method,
+                    path,
+                    request: no_leading_dot(&rpc.request_type.borrow()).to_string(),
+                    response: no_leading_dot(&rpc.response_type.borrow()).to_string(),
+                    streaming: rpc.request_stream || rpc.response_stream,
+                });
+            }
+        }
+    }
+
+    for child in ns.nested.values() {
+        populate_flat(routes, child)
+    }
+}
+
 /// Recursively populate the service tree map with the given namespace
-fn populate<'a, 'b>(src: &'b Cell<ServiceTreeMap<'a>>, ns: &'a Namespace) {
+fn populate<'a>(src: &Cell<ServiceTreeMap<'a>>, ns: &'a Namespace, config: &ServiceMapConfig) {
     let mut map = src.take();
 
     for service in ns.services.values() {
         for (name, rpc) in service.methods.iter() {
-            let (segments, last_segment, url) = match HTTPOptions::from(&rpc.md.options) {
-                Some(HTTPOptions { method, path, .. }) => (
-                    path.split('/')
-                        .skip(1)
-                        .map(|seg| match seg.starts_with(':') {
-                            true => Cow::from("*"),
-                            false => Cow::from(seg.to_string()),
-                        })
-                        .collect::<Vec<_>>(),
-                    Cow::from(method.to_lowercase()),
-                    path,
-                ),
-                None => {
-                    let segments = vec![Cow::from(ns.path.join(".")), name.into()];
-                    let url = format!("/{}", segments.join("/"));
-                    (segments, Cow::from("grpc"), Cow::from(url))
-                }
-            };
+            if !config.include_streaming && (rpc.request_stream || rpc.response_stream) {
+                continue;
+            }
 
-            let mut ptr = &mut map;
+            for (http_method, url) in rpc_routes(ns, name, rpc) {
+                let mut ptr = &mut map;
 
-            for path in segments {
-                ptr = ptr
-                    .entry(path)
-                    .or_insert_with(|| ServiceMapNode::Branch(BTreeMap::new()))
-                    .unwrap_as_branch();
-            }
+                let segments = url
+                    .split('/')
+                    .skip(1)
+                    .map(|seg| match (seg.starts_with(':'), config.path_style) {
+                        (true, PathStyle::Wildcard) => Cow::from("*"),
+                        _ => Cow::from(seg.to_string()),
+                    })
+                    .collect::<Vec<_>>();
+
+                for path in segments {
+                    ptr = ptr
+                        .entry(path)
+                        .or_insert_with(|| ServiceMapNode::Branch(BTreeMap::new()))
+                        .unwrap_as_branch();
+                }
 
-            ptr.insert(last_segment, ServiceMapNode::Leaf { rpc, url });
+                ptr.insert(http_method, ServiceMapNode::Leaf { rpc, url });
+            }
         }
     }
 
     src.set(map);
     for child in ns.nested.values() {
-        populate(src, child)
+        populate(src, child, config)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{parser::test_util::parse_test_file, service_map::no_leading_dot};
+    use crate::{
+        parser::test_util::parse_test_file,
+        service_map::{no_leading_dot, PathStyle, ServiceMapConfig},
+    };
     use indoc::indoc;
     use pretty_assertions::assert_eq;
 
@@ -185,4 +299,142 @@ mod tests {
 
         assert_eq!(output, result);
     }
+
+    #[test]
+    fn test_multiple_http_bindings() {
+        let ns = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        service HelloWorld {
+          rpc SayHello (SayHelloRequest) returns (SayHelloResponse) {
+            option (pgm.http.rule) = { GET: "/hello", POST: "/hello" };
+          }
+        }
+
+        message SayHelloRequest {}
+        message SayHelloResponse {}
+        "#});
+
+        let map = super::create(&ns);
+        let output = serde_json::to_value(&map).unwrap();
+
+        for method in ["get", "post"] {
+            assert_eq!(
+                output["hello"][method],
+                serde_json::json!([
+                    "pb.hello.SayHelloRequest",
+                    "pb.hello.SayHelloResponse",
+                    "/hello"
+                ])
+            );
+        }
+    }
+
+    #[test]
+    fn test_package_less_service() {
+        let ns = parse_test_file(indoc! {r#"
+        service HelloWorld {
+          rpc SayHello (SayHelloRequest) returns (SayHelloResponse) {}
+        }
+
+        message SayHelloRequest {}
+        message SayHelloResponse {}
+        "#});
+
+        let map = super::create(&ns);
+        let output = serde_json::to_value(&map).unwrap();
+
+        assert_eq!(
+            output,
+            serde_json::json!({
+                "SayHello": {
+                    "grpc": ["SayHelloRequest", "SayHelloResponse", "/SayHello"]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_service_map_config() {
+        let ns = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        service HelloWorld {
+          rpc LotsOfGreetings(stream SayHelloRequest) returns (SayHelloResponse) {}
+          rpc SayHello (SayHelloRequest) returns (SayHelloResponse) { option (pgm.http.rule) = { GET: "/hello/<string:name>" }; }
+        }
+
+        message SayHelloRequest {}
+        message SayHelloResponse {}
+        "#});
+
+        let config = ServiceMapConfig {
+            path_style: PathStyle::Params,
+            include_streaming: false,
+        };
+
+        let map = super::create_with(&ns, &config);
+        let output = serde_json::to_string_pretty(&map).unwrap();
+
+        let result = indoc! {r#"
+          {
+            "hello": {
+              ":name": {
+                "get": [
+                  "pb.hello.SayHelloRequest",
+                  "pb.hello.SayHelloResponse",
+                  "/hello/:name"
+                ]
+              }
+            }
+          }"#};
+
+        assert_eq!(output, result);
+        assert_eq!(
+            serde_json::to_string(&ns.to_service_map(&ServiceMapConfig::default())).unwrap(),
+            serde_json::to_string(&super::create(&ns)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_generate_flat_service_map() {
+        let ns = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        service HelloWorld {
+          rpc LotsOfGreetings(stream SayHelloRequest) returns (SayHelloResponse) {}
+          rpc SayHello (SayHelloRequest) returns (SayHelloResponse) { option (pgm.http.rule) = { GET: "/hello/<string:name>" }; }
+        }
+
+        message SayHelloRequest {}
+        message SayHelloResponse {}
+        "#});
+
+        let routes = super::create_flat(&ns);
+        let output = serde_json::to_value(&routes).unwrap();
+
+        assert_eq!(
+            output,
+            serde_json::json!([
+                {
+                    "service": "pb.hello.HelloWorld",
+                    "method": "LotsOfGreetings",
+                    "http_method": "grpc",
+                    "path": "/pb.hello/LotsOfGreetings",
+                    "request": "pb.hello.SayHelloRequest",
+                    "response": "pb.hello.SayHelloResponse",
+                    "streaming": true
+                },
+                {
+                    "service": "pb.hello.HelloWorld",
+                    "method": "SayHello",
+                    "http_method": "get",
+                    "path": "/hello/:name",
+                    "request": "pb.hello.SayHelloRequest",
+                    "response": "pb.hello.SayHelloResponse",
+                    "streaming": false
+                }
+            ])
+        );
+    }
 }
@@ -0,0 +1,27 @@
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+/// Syntax represents the proto [syntax] version of a file
+/// Files without a syntax statement default to proto2
+///
+/// [syntax]: https://developers.google.com/protocol-buffers/docs/proto3#simple
+#[derive(Display, Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Syntax {
+    #[default]
+    #[display(fmt = "proto2")]
+    Proto2,
+
+    #[display(fmt = "proto3")]
+    Proto3,
+}
+
+impl Syntax {
+    /// Returns the syntax matching the given version string if it's supported
+    pub fn from_version(version: &str) -> Option<Self> {
+        match version {
+            "proto2" => Some(Self::Proto2),
+            "proto3" => Some(Self::Proto3),
+            _ => None,
+        }
+    }
+}
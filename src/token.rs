@@ -2,6 +2,7 @@ use crate::{field::FieldRule, parse_error::ParseError};
 use derive_more::Display;
 
 // TODO add lifetime and take &'a str instead of String
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Display, Debug, PartialEq)]
 pub enum Token {
     #[display(fmt = "EOF")]
@@ -12,6 +12,21 @@ pub struct Tokenizer<I: Iterator> {
 
     /// The current comment if any
     pub comment: Option<Comment>,
+
+    /// The last comment starting on the same line as the token preceding it, e.g `FOO = 1; // foo`
+    pub trailing_comment: Option<Comment>,
+
+    /// When set, comments that are replaced before being attached to a definition are collected here
+    pub floating_comments: Option<Vec<Comment>>,
+
+    /// The start position of the last returned token
+    token_position: Position,
+
+    /// The line where the last read token ends, used to tell trailing comments apart
+    last_token_line: usize,
+
+    /// The peeked token and its start position, see [Tokenizer::peek]
+    peeked: Option<(Result<Token, TokenError>, Position)>,
 }
 
 impl<I: Iterator<Item = char>> Tokenizer<I> {
@@ -20,6 +35,11 @@ impl<I: Iterator<Item = char>> Tokenizer<I> {
         Self {
             chars: IteratorWithPosition::new(chars),
             comment: None,
+            trailing_comment: None,
+            floating_comments: None,
+            token_position: Position::default(),
+            last_token_line: 0,
+            peeked: None,
         }
     }
 
@@ -33,6 +53,11 @@ impl<I: Iterator<Item = char>> Tokenizer<I> {
         self.chars.current_position()
     }
 
+    /// Returns the start position of the last returned token
+    pub fn token_position(&self) -> Position {
+        self.token_position.clone()
+    }
+
     /// Skip tokens until it matches the passed token
     pub fn skip_until_token(&mut self, token: Token) -> Result<(), TokenError> {
         loop {
@@ -124,8 +149,16 @@ impl<I: Iterator<Item = char>> Tokenizer<I> {
         }
     }
 
-    /// Return the next comment
-    fn read_comment(&mut self) -> Result<Comment, TokenError> {
+    /// Record a comment that is not attached to any definition, when floating comments are collected
+    pub fn add_floating_comment(&mut self, comment: Comment) {
+        if let Some(floating_comments) = self.floating_comments.as_mut() {
+            floating_comments.push(comment);
+        }
+    }
+
+    /// Return the next comment.
+    /// Separator line comments (e.g `////////` or `// -----`) are skipped and return None
+    fn read_comment(&mut self) -> Result<Option<Comment>, TokenError> {
         let char = self.chars.next().ok_or(TokenError::EOF)?;
         let start_line = self.current_line();
 
@@ -134,8 +167,8 @@ impl<I: Iterator<Item = char>> Tokenizer<I> {
             '*' => {
                 let mut previous_char = self.chars.next().ok_or(TokenError::EOF)?;
 
-                // ignore second * for block comments starting with /**
-                if previous_char == '*' {
+                // ignore doc markers for block comments starting with /** or /*!
+                if matches!(previous_char, '*' | '!') {
                     previous_char = self.chars.next().ok_or(TokenError::EOF)?;
                 }
 
@@ -146,11 +179,11 @@ impl<I: Iterator<Item = char>> Tokenizer<I> {
                     match (previous_char, current_char) {
                         // return comment when we get a */
                         ('*', '/') => {
-                            return Ok(Comment::star_slash(
+                            return Ok(Some(Comment::star_slash(
                                 comment,
                                 start_line,
                                 self.current_line(),
-                            ));
+                            )));
                         }
 
                         // skip \r
@@ -176,11 +209,11 @@ impl<I: Iterator<Item = char>> Tokenizer<I> {
                     }
                 }
 
-                Ok(Comment::star_slash(
+                Ok(Some(Comment::star_slash(
                     comment,
                     start_line,
                     self.current_line(),
-                ))
+                )))
             }
 
             // // double slash comment
@@ -192,13 +225,30 @@ impl<I: Iterator<Item = char>> Tokenizer<I> {
                         comment.push(c);
                     } else {
                         stripped_first_slash = true;
-                        if c != '/' {
+
+                        // ignore doc markers for line comments starting with /// or //!
+                        if !matches!(c, '/' | '!') {
                             comment.push(c);
                         }
                     }
                 }
 
-                Ok(match self.comment.take() {
+                // strip the carriage return of CRLF line endings
+                if comment.ends_with('\r') {
+                    comment.pop();
+                }
+
+                if is_separator(&comment) {
+                    // a separator closing a comment block keeps the block attached to what follows
+                    if let Some(previous) = self.comment.as_mut() {
+                        if previous.end_line == start_line - 1 {
+                            previous.end_line = start_line;
+                        }
+                    }
+                    return Ok(None);
+                }
+
+                Ok(Some(match self.comment.take() {
                     // Concat with the previous double slash comment if it directly preceed this one
                     Some(Comment {
                         kind: CommentKind::DoubleSlash,
@@ -211,17 +261,55 @@ impl<I: Iterator<Item = char>> Tokenizer<I> {
                         previous_start_line,
                         start_line,
                     ),
-                    _ => Comment::double_slash(comment, start_line, start_line),
-                })
+                    previous => {
+                        if let Some(previous) = previous {
+                            self.add_floating_comment(previous);
+                        }
+                        Comment::double_slash(comment, start_line, start_line)
+                    }
+                }))
             }
 
             found => Err(TokenError::UnexpectedChar(found)),
         }
     }
 
+    /// Returns the next token without consuming it.
+    /// Note: the comments preceding the peeked token are read, and the current position is moved after it
+    pub fn peek(&mut self) -> Result<&Token, TokenError> {
+        if self.peeked.is_none() {
+            // keep the position of the last returned token until the peeked token is returned
+            let token_position = self.token_position.clone();
+            let token = self.read_token();
+            let position = std::mem::replace(&mut self.token_position, token_position);
+            self.peeked = Some((token, position));
+        }
+
+        match self.peeked.as_ref() {
+            Some((Ok(token), _)) => Ok(token),
+            Some((Err(error), _)) => Err(error.clone()),
+            None => unreachable!(),
+        }
+    }
+
     /// Returns the next token
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Result<Token, TokenError> {
-        match self.chars.next() {
+        match self.peeked.take() {
+            Some((token, position)) => {
+                self.token_position = position;
+                token
+            }
+            None => self.read_token(),
+        }
+    }
+
+    /// Read the next token from the chars iterator
+    fn read_token(&mut self) -> Result<Token, TokenError> {
+        // whitespaces and comments are skipped recursively, the last call sets the token position
+        self.token_position = self.chars.current_position();
+
+        let token = match self.chars.next() {
             None => Ok(Token::EOF),
 
             Some('=') => Ok(Token::Eq),
@@ -238,12 +326,30 @@ impl<I: Iterator<Item = char>> Tokenizer<I> {
             Some(',') => Ok(Token::Comma),
 
             // whitespace or New line
-            Some(' ') | Some('\t') | Some('\r') | Some('\n') => self.next(),
+            Some(' ') | Some('\t') | Some('\r') | Some('\n') => return self.read_token(),
 
             // comment
+            Some('/') if self.current_line() == self.last_token_line => {
+                // comments starting on the line of the previous token trail it (e.g `FOO = 1; // foo`)
+                let previous_comment = self.comment.take();
+                let comment = self.read_comment()?;
+                self.comment = previous_comment;
+
+                if let Some(comment) = comment {
+                    if let Some(previous) = self.trailing_comment.replace(comment) {
+                        self.add_floating_comment(previous);
+                    }
+                }
+                return self.read_token();
+            }
             Some('/') => {
-                self.comment = Some(self.read_comment()?);
-                self.next()
+                // separators yield no comment and leave the current one in place
+                if let Some(comment) = self.read_comment()? {
+                    if let Some(previous) = self.comment.replace(comment) {
+                        self.add_floating_comment(previous);
+                    }
+                }
+                return self.read_token();
             }
 
             // Quoted string
@@ -251,10 +357,64 @@ impl<I: Iterator<Item = char>> Tokenizer<I> {
 
             // word
             Some(c) => Ok(self.read_identifier(c)),
-        }
+        };
+
+        self.last_token_line = self.current_line();
+        token
     }
 }
 
+/// Returns true if the comment text is only made of punctuation, like section separators
+fn is_separator(text: &str) -> bool {
+    let text = text.trim();
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_punctuation())
+}
+
+/// Returns an iterator over the tokens of the given input, along with their start position.
+/// The iterator ends at the end of the input, or after the first error.
+///
+/// # Example:
+///
+/// ```
+/// use prosecco::{tokenize, Token};
+///
+/// let tokens = tokenize("package pb.hello;")
+///     .map(|r| r.map(|(token, position)| (token, position.column)))
+///     .collect::<Result<Vec<_>, _>>()
+///     .expect("it should tokenize");
+///
+/// assert_eq!(
+///     tokens,
+///     vec![
+///         (Token::Package, 1),
+///         (Token::Identifier("pb.hello".to_string()), 9),
+///         (Token::Semi, 17),
+///     ]
+/// );
+/// ```
+pub fn tokenize(input: &str) -> impl Iterator<Item = Result<(Token, Position), TokenError>> + '_ {
+    let mut tokenizer = Tokenizer::new(input.chars());
+    let mut done = false;
+
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+
+        match tokenizer.next() {
+            Ok(Token::EOF) => {
+                done = true;
+                None
+            }
+            Ok(token) => Some(Ok((token, tokenizer.token_position()))),
+            Err(error) => {
+                done = true;
+                Some(Err(error))
+            }
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tokenizer::Tokenizer;
@@ -289,6 +449,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_should_parse_inner_doc_line_comment() -> Result<(), TokenError> {
+        let mut tokenizer = Tokenizer::new("//! hello world".chars());
+        tokenizer.next()?;
+        assert_eq!(
+            tokenizer.comment.map(|c| c.text),
+            Some(" hello world".into())
+        );
+        Ok(())
+    }
+
     #[test]
     fn it_should_parse_multiline_double_slash_comment() -> Result<(), TokenError> {
         let mut tokenizer = Tokenizer::new("// hello\n// world".chars());
@@ -300,6 +471,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_should_strip_carriage_return_from_double_slash_comment() -> Result<(), TokenError> {
+        let mut tokenizer = Tokenizer::new("// hello\r\n// world\r\nint32".chars());
+        tokenizer.next()?;
+        assert_eq!(
+            tokenizer.comment.map(|c| c.text),
+            Some(" hello\n world".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_skip_separator_comments() -> Result<(), TokenError> {
+        let mut tokenizer = Tokenizer::new("////////////\n// hello\nint32 foo = 1;".chars());
+        tokenizer.next()?;
+        assert_eq!(tokenizer.comment.map(|c| c.text), Some(" hello".into()));
+
+        let mut tokenizer = Tokenizer::new("// ----------\nint32 foo = 1;".chars());
+        tokenizer.next()?;
+        assert_eq!(tokenizer.comment.map(|c| c.text), None);
+
+        let mut tokenizer = Tokenizer::new("// hello\n// ----------\nint32 foo = 1;".chars());
+        tokenizer.next()?;
+        assert_eq!(
+            tokenizer.comment.map(|c| (c.text, c.end_line)),
+            Some((" hello".into(), 2))
+        );
+        Ok(())
+    }
+
     #[test]
     fn it_should_parse_slash_star_comment() -> Result<(), TokenError> {
         let mut tokenizer = Tokenizer::new("/* hello world */".chars());
@@ -312,6 +513,18 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_should_parse_inner_doc_block_comment() -> Result<(), TokenError> {
+        let mut tokenizer = Tokenizer::new("/*! hello world */".chars());
+        tokenizer.next()?;
+
+        assert_eq!(
+            tokenizer.comment.map(|c| c.text),
+            Some(" hello world ".into())
+        );
+        Ok(())
+    }
+
     #[test]
     fn it_should_parse_doc_string() -> Result<(), TokenError> {
         let comment = r#"
@@ -330,4 +543,35 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn it_should_peek_the_next_token() -> Result<(), TokenError> {
+        let mut tokenizer = Tokenizer::new("package  pb;\n\"unterminated".chars());
+
+        assert_eq!(tokenizer.peek()?, &Token::Package);
+        assert_eq!(tokenizer.peek()?, &Token::Package);
+        assert_eq!(tokenizer.next()?, Token::Package);
+        assert_eq!(tokenizer.token_position().column, 1);
+
+        assert_eq!(tokenizer.peek()?, &Token::Identifier("pb".to_string()));
+        assert_eq!(tokenizer.token_position().column, 1);
+        assert_eq!(tokenizer.next()?, Token::Identifier("pb".to_string()));
+        assert_eq!(tokenizer.token_position().column, 10);
+
+        assert_eq!(tokenizer.next()?, Token::Semi);
+        assert_eq!(tokenizer.peek(), Err(TokenError::MissingEndDelimiter('"')));
+        assert_eq!(tokenizer.next(), Err(TokenError::MissingEndDelimiter('"')));
+        Ok(())
+    }
+
+    #[test]
+    fn it_should_parse_leading_dot_identifier() -> Result<(), TokenError> {
+        let mut tokenizer = Tokenizer::new(".google.protobuf.Any any = 1;".chars());
+        assert_eq!(
+            tokenizer.next()?,
+            Token::Identifier(".google.protobuf.Any".to_string())
+        );
+        assert_eq!(tokenizer.next()?, Token::Identifier("any".to_string()));
+        Ok(())
+    }
 }
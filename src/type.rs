@@ -13,7 +13,7 @@ pub enum Type {
 
 impl Type {
     /// Get the nested type with the provided key
-    pub fn get<'a>(&'a self, key: &str) -> Option<&Type> {
+    pub fn get(&self, key: &str) -> Option<&Type> {
         match self {
             Type::Enum(_) => None,
             Type::Message(msg) => msg.nested.get(key),
@@ -0,0 +1,104 @@
+//! Generate the `index.ts` companion of a Typescript definition file
+//!
+//! The definitions generated by [crate::typescript::serializer::Printer] are declared in the global scope,
+//! the index file re-exports each top-level namespace so that they can also be imported from a module.
+
+use crate::namespace::Namespace;
+
+/// Returns the content of an `index.ts` file re-exporting each top-level namespace of the given root
+///
+/// # Example:
+///
+/// For a root namespace containing `pb.hello` and `google.protobuf`, the output is:
+///
+/// ```ts
+/// import _google = google
+/// import _pb = pb
+///
+/// export { _google as google, _pb as pb }
+/// ```
+pub fn index_exports(root: &Namespace) -> String {
+    let names = root
+        .nested
+        .iter()
+        .filter(|(_, ns)| !ns.is_empty())
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+
+    // an empty export is still needed to make the file a module
+    if names.is_empty() {
+        return "export {}\n".to_string();
+    }
+
+    let mut output = String::new();
+    for name in names.iter() {
+        output.push_str(&format!("import _{name} = {name}\n", name = name));
+    }
+
+    let exports = names
+        .iter()
+        .map(|name| format!("_{name} as {name}", name = name))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    output.push_str(&format!("\nexport {{ {} }}\n", exports));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::index_exports;
+    use crate::{
+        namespace::Namespace,
+        parser::{test_util::virtual_fs, Parser},
+    };
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_index_exports() {
+        let hello = indoc! {r#"
+        package pb.hello;
+
+        import "google/protobuf/empty.proto";
+
+        service HelloWorld {
+          rpc SayHello (SayHelloRequest) returns (SayHelloResponse) {}
+          rpc Ping (google.protobuf.Empty) returns (google.protobuf.Empty) {}
+        }
+
+        message SayHelloRequest {
+          string name = 1;
+        }
+
+        message SayHelloResponse {
+          string hello = 1;
+        }
+        "#};
+
+        let files = virtual_fs(&[
+            ("hello.proto", hello),
+            (
+                "google/protobuf/empty.proto",
+                "package google.protobuf; message Empty {}",
+            ),
+        ]);
+
+        let mut parser = Parser::with_virtual_fs(files);
+        parser
+            .parse_file(PathBuf::from("hello.proto"))
+            .expect("it should parse hello.proto");
+        let root = parser.build_root().expect("it should build root");
+
+        let expected = indoc! {r#"
+        import _google = google
+        import _pb = pb
+
+        export { _google as google, _pb as pb }
+        "#};
+
+        assert_eq!(index_exports(&root), expected);
+        assert_eq!(index_exports(&Namespace::default()), "export {}\n");
+    }
+}
@@ -91,4 +91,8 @@
 //! }
 
 mod constants;
+mod index;
 pub mod serializer;
+
+pub use index::index_exports;
+pub use serializer::ts_type;
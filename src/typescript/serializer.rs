@@ -1,20 +1,301 @@
 use super::constants::TYPE_MAPPING;
 use crate::{
-    field::FieldRule, http_options::HTTPOptions, message::Message, metadata::Metadata,
-    namespace::Namespace, r#enum::Enum, r#type::Type, service::Rpc, typescript::constants::*,
+    field::{FieldRule, JsType},
+    http_options::{HTTPBinding, HTTPOptions},
+    message::Message,
+    metadata::Metadata,
+    namespace::Namespace,
+    r#enum::Enum,
+    r#type::Type,
+    service::Rpc,
+    typescript::constants::*,
 };
 use convert_case::{Case, Casing};
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     fmt::Write,
+    path::Path,
+    rc::Rc,
 };
 
+/// A function that returns the URL of a proto definition, given its file path and line
+pub type LinkResolver = Box<dyn Fn(&Path, usize) -> String>;
+
 /// PrintOptions let us configure How we want to print a Proto tree into a Typescript definition file
 pub struct PrintConfig {
     pub root_url: String,
+
+    /// Resolve the `@link` URL of definitions, when not set links are built from `root_url`
+    pub link_resolver: Option<LinkResolver>,
+
     pub print_bubble_client: bool,
     pub print_network_client: bool,
+
+    /// How repeated fields are printed
+    pub array_style: ArrayStyle,
+
+    /// How enums are printed
+    pub enum_style: EnumStyle,
+
+    /// How field names are printed
+    pub field_case: FieldCase,
+
+    /// How messages are printed
+    pub message_style: MessageStyle,
+
+    /// How messages without fields are printed
+    pub empty_message_style: EmptyMessageStyle,
+
+    /// Emit a JSDoc `@see` link to the referenced type of message and enum fields
+    pub emit_see_links: bool,
+
+    /// Emit a JSDoc `@default` tag for fields with a proto2 default value
+    pub emit_defaults: bool,
+
+    /// How the definitions are exposed, as global declarations or as an ES module
+    pub output_style: OutputStyle,
+
+    /// A header printed verbatim at the top of the generated files (e.g `// GENERATED - DO NOT EDIT`)
+    pub header: Option<String>,
+}
+
+impl Default for PrintConfig {
+    fn default() -> Self {
+        Self {
+            root_url: String::new(),
+            link_resolver: None,
+            print_bubble_client: true,
+            print_network_client: true,
+            array_style: ArrayStyle::default(),
+            enum_style: EnumStyle::default(),
+            field_case: FieldCase::default(),
+            message_style: MessageStyle::default(),
+            empty_message_style: EmptyMessageStyle::default(),
+            emit_see_links: false,
+            emit_defaults: false,
+            output_style: OutputStyle::default(),
+            header: None,
+        }
+    }
+}
+
+impl PrintConfig {
+    /// Returns a builder used to configure a PrintConfig fluently
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use prosecco::typescript::serializer::{ArrayStyle, PrintConfig};
+    ///
+    /// let config = PrintConfig::builder()
+    ///     .root_url("https://github.com/lyft/idl/blob/master/protos")
+    ///     .print_network_client(false)
+    ///     .array_style(ArrayStyle::ReadonlyArray)
+    ///     .build();
+    ///
+    /// assert!(config.print_bubble_client);
+    /// assert!(!config.print_network_client);
+    /// ```
+    pub fn builder() -> PrintConfigBuilder {
+        PrintConfigBuilder::default()
+    }
+}
+
+/// A builder for [PrintConfig], fields that are not set use the default configuration
+#[derive(Default)]
+pub struct PrintConfigBuilder {
+    config: PrintConfig,
+}
+
+impl PrintConfigBuilder {
+    /// Set the root url used to print links to the proto source files
+    pub fn root_url(mut self, root_url: impl Into<String>) -> Self {
+        self.config.root_url = root_url.into();
+        self
+    }
+
+    /// Set the function used to resolve the `@link` URL of definitions
+    pub fn link_resolver(mut self, resolver: impl Fn(&Path, usize) -> String + 'static) -> Self {
+        self.config.link_resolver = Some(Box::new(resolver));
+        self
+    }
+
+    /// Set whether @lyft/bubble-client definitions are printed
+    pub fn print_bubble_client(mut self, enable: bool) -> Self {
+        self.config.print_bubble_client = enable;
+        self
+    }
+
+    /// Set whether @lyft/network-client definitions are printed
+    pub fn print_network_client(mut self, enable: bool) -> Self {
+        self.config.print_network_client = enable;
+        self
+    }
+
+    /// Set how repeated fields are printed
+    pub fn array_style(mut self, array_style: ArrayStyle) -> Self {
+        self.config.array_style = array_style;
+        self
+    }
+
+    /// Set how enums are printed
+    pub fn enum_style(mut self, enum_style: EnumStyle) -> Self {
+        self.config.enum_style = enum_style;
+        self
+    }
+
+    /// Set how field names are printed
+    pub fn field_case(mut self, field_case: FieldCase) -> Self {
+        self.config.field_case = field_case;
+        self
+    }
+
+    /// Set how messages are printed
+    pub fn message_style(mut self, message_style: MessageStyle) -> Self {
+        self.config.message_style = message_style;
+        self
+    }
+
+    /// Set how messages without fields are printed
+    pub fn empty_message_style(mut self, empty_message_style: EmptyMessageStyle) -> Self {
+        self.config.empty_message_style = empty_message_style;
+        self
+    }
+
+    /// Set whether a JSDoc `@see` link to the referenced type is printed for message and enum fields
+    pub fn emit_see_links(mut self, enable: bool) -> Self {
+        self.config.emit_see_links = enable;
+        self
+    }
+
+    /// Set whether a JSDoc `@default` tag is printed for fields with a proto2 default value
+    pub fn emit_defaults(mut self, enable: bool) -> Self {
+        self.config.emit_defaults = enable;
+        self
+    }
+
+    /// Set how the definitions are exposed, see [OutputStyle]
+    pub fn output_style(mut self, output_style: OutputStyle) -> Self {
+        self.config.output_style = output_style;
+        self
+    }
+
+    /// Set the header printed at the top of the generated files
+    pub fn header(mut self, header: impl Into<String>) -> Self {
+        self.config.header = Some(header.into());
+        self
+    }
+
+    /// Returns the configuration
+    pub fn build(self) -> PrintConfig {
+        self.config
+    }
+}
+
+/// ArrayStyle defines how repeated fields are printed
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ArrayStyle {
+    /// Array<T>
+    #[default]
+    Array,
+
+    /// ReadonlyArray<T>
+    ReadonlyArray,
+
+    /// T[]
+    Brackets,
+}
+
+impl ArrayStyle {
+    /// Returns the array type for the given item type
+    fn format(&self, type_name: &str) -> String {
+        match self {
+            Self::Array => format!("Array<{}>", type_name),
+            Self::ReadonlyArray => format!("ReadonlyArray<{}>", type_name),
+            Self::Brackets if type_name.contains(' ') => format!("({})[]", type_name),
+            Self::Brackets => format!("{}[]", type_name),
+        }
+    }
+}
+
+/// EnumStyle defines how enums are printed
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EnumStyle {
+    /// const enum Status { A = 0, B = 1 }
+    #[default]
+    ConstEnum,
+
+    /// enum Status { A = 0, B = 1 }
+    Enum,
+
+    /// type Status = 'A' | 'B'
+    UnionType,
+}
+
+/// MessageStyle defines how messages are printed
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MessageStyle {
+    /// interface Foo { bar?: string }
+    #[default]
+    Interface,
+
+    /// type Foo = { bar?: string }
+    Type,
+}
+
+/// EmptyMessageStyle defines how messages without fields are printed
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EmptyMessageStyle {
+    /// interface Foo extends Empty {}, with the global Empty type
+    #[default]
+    Empty,
+
+    /// interface Foo extends Record<string, never> {}
+    Record,
+
+    /// interface Foo {}
+    Object,
+}
+
+/// OutputStyle defines how the definitions are exposed
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputStyle {
+    /// declare global { namespace pb { interface Foo {} } }
+    #[default]
+    Global,
+
+    /// export namespace pb { export interface Foo {} }
+    Module,
+}
+
+/// FieldCase defines how field names are printed.
+///
+/// Note: The descriptors always use the proto field names.
+/// When loading them with protobuf.js, use `FieldCase::Camel` unless the `keepCase` option is set,
+/// as protobuf.js converts field names to their camelCase json_name by default.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FieldCase {
+    /// Field names are printed as defined in the proto file
+    #[default]
+    AsIs,
+
+    /// snake_case_field => snakeCaseField
+    Camel,
+
+    /// snakeCaseField => snake_case_field
+    Snake,
+}
+
+impl FieldCase {
+    /// Returns the field name converted to the configured case
+    fn convert<'b>(&self, name: &'b str) -> Cow<'b, str> {
+        match self {
+            Self::AsIs => name.into(),
+            Self::Camel => name.to_case(Case::Camel).into(),
+            Self::Snake => name.to_case(Case::Snake).into(),
+        }
+    }
 }
 
 /// Printer serialize a Proto namespace into an internal buffer
@@ -30,6 +311,25 @@ pub struct Printer<'a> {
 
     /// The indent level
     indent: usize,
+
+    /// How the definitions are exposed, see [PrintConfig::output_style]
+    output_style: OutputStyle,
+
+    /// When set, the source of each printed declaration is recorded here, see [Printer::into_string_with_source_map]
+    source_map: Option<Vec<SourceMapEntry>>,
+}
+
+/// Map a line of the Typescript output to the proto definition it was generated from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceMapEntry {
+    /// The line of the declaration in the output, starting at 1
+    pub line: usize,
+
+    /// The proto file of the definition
+    pub file_path: Rc<Path>,
+
+    /// The line of the definition in the proto file
+    pub source_line: usize,
 }
 
 /// write! wrapper that write to the printer buffer
@@ -78,21 +378,96 @@ impl<'a> Printer<'a> {
             includes: HashSet::new(),
             config,
             indent: 0,
+            output_style: config.output_style,
+            source_map: None,
         }
     }
 
     /// Create a Typescript definition file
     pub fn into_string(mut self, root: &'a Namespace) -> String {
+        // types defined in package-less files live directly in the root namespace
+        self.write_file(root.types.iter(), root.nested.iter(), root, &[]);
+        self.into_output()
+    }
+
+    /// Create a Typescript definition file, along with the source of each message, field and enum declaration
+    pub fn into_string_with_source_map(
+        mut self,
+        root: &'a Namespace,
+    ) -> (String, Vec<SourceMapEntry>) {
+        self.source_map = Some(Vec::new());
+        self.write_file(root.types.iter(), root.nested.iter(), root, &[]);
+
+        let source_map = self.source_map.take().unwrap_or_default();
+        (self.into_output(), source_map)
+    }
+
+    /// Create an ES module per top-level namespace, returned as a map of namespace name => module content.
+    /// References to the other top-level namespaces are imported from their relative module (e.g `./google`).
+    ///
+    /// Note: types defined in package-less files don't belong to any module, use [Printer::into_string] to print them
+    pub fn into_modules(self, root: &'a Namespace) -> BTreeMap<String, String> {
+        let include_rpcs = self.config.print_bubble_client || self.config.print_network_client;
+
+        root.nested
+            .iter()
+            .filter(|(_, ns)| !ns.is_empty())
+            .map(|(name, ns)| {
+                let mut printer = self.printer_with_config(0);
+                printer.output_style = OutputStyle::Module;
+
+                let mut references = BTreeSet::new();
+                add_references(ns, include_rpcs, &mut references);
+                let imports = references
+                    .into_iter()
+                    .filter(|reference| reference != name)
+                    .map(|reference| format!("import {{ {0} }} from './{0}'", reference))
+                    .collect::<Vec<_>>();
+
+                printer.write_file(
+                    std::iter::empty(),
+                    std::iter::once((name, ns)),
+                    ns,
+                    &imports,
+                );
+                (name.clone(), printer.into_output())
+            })
+            .collect()
+    }
+
+    /// Write the definitions of the given types and namespaces, and of the rpcs defined in `services_root`.
+    /// The given imports are printed after the client imports
+    fn write_file(
+        &mut self,
+        types: impl Iterator<Item = (&'a String, &'a Type)>,
+        namespaces: impl Iterator<Item = (&'a String, &'a Namespace)>,
+        services_root: &'a Namespace,
+        imports: &[String],
+    ) {
+        if let Some(header) = &self.config.header {
+            self.buffer.push_str(header);
+            if !header.ends_with('\n') {
+                self.buffer.push('\n');
+            }
+        }
+
+        // global definitions are nested in a `declare global` block
+        let types_indent = match self.output_style {
+            OutputStyle::Global => 2,
+            OutputStyle::Module => 0,
+        };
+
         let mut network_client_printer = self.printer_with_config(4);
         let mut bubble_client_printer = self.printer_with_config(4);
-        let mut types_printer = self.printer_with_config(2);
+        let mut types_printer = self.printer_with_config(types_indent);
         let mut includes: HashSet<&'static str> = HashSet::new();
 
         // write messages typescript definitions
-        types_printer.write_namespaces(&root.nested);
+        types_printer.write_types(types);
+        types_printer.write_namespaces(namespaces);
 
         // write services definitions
-        for_each_rpc(root, &mut |ns, method_name, rpc| {
+        for_each_rpc(services_root, &mut |ns, method_name, rpc| {
             network_client_printer.write_network_client_rpc(ns, method_name, rpc);
             bubble_client_printer.write_bubble_client_rpc(ns, method_name, rpc);
         });
@@ -114,7 +489,9 @@ impl<'a> Printer<'a> {
             if enable && !printer.buffer.is_empty() {
                 includes.insert(import);
             } else {
-                printer.buffer.clear()
+                // drop the client only includes (e.g rxjs Observable) along with the definitions
+                printer.buffer.clear();
+                printer.includes.clear();
             }
         }
 
@@ -128,7 +505,7 @@ impl<'a> Printer<'a> {
         }
 
         // print imports from includes
-        std::array::IntoIter::new([
+        IntoIterator::into_iter([
             OBSERVABLE_IMPORT,
             BUBBLE_CLIENT_IMPORT,
             NETWORK_CLIENT_IMPORT,
@@ -136,6 +513,10 @@ impl<'a> Printer<'a> {
         .filter(|import| includes.contains(import))
         .for_each(|import| writeln!(self, import));
 
+        for import in imports {
+            writeln!(self, import);
+        }
+
         // print @lyft/bubble-client definitions
         if !bubble_client_printer.buffer.is_empty() {
             writeln_and_indent!(self, "declare module '@lyft/bubble-client' {");
@@ -154,22 +535,57 @@ impl<'a> Printer<'a> {
             outdent_and_writeln!(self, "}");
         }
 
-        writeln!(self, "declare global {");
+        // global types from includes
+        let global_types = IntoIterator::into_iter([&LONG_LIKE_TYPE, &ANY_TYPE, &EMPTY])
+            .filter(|val| includes.contains(*val));
 
-        // print global types from includes
-        std::array::IntoIter::new([&LONG_LIKE_TYPE, &ANY_TYPE, &EMPTY])
-            .filter(|val| includes.contains(*val))
-            .for_each(|val| writeln!(self, val));
+        match self.output_style {
+            OutputStyle::Global => {
+                writeln!(self, "declare global {");
+                global_types.for_each(|val| writeln!(self, val));
+                self.add_blank_line();
+                self.append(types_printer);
+                writeln!(self, "}");
+            }
+            OutputStyle::Module => {
+                // the global types are defined with the indentation of the `declare global` block
+                for val in global_types {
+                    for line in val.lines() {
+                        let line = line.strip_prefix("  ").unwrap_or(line).trim_end();
 
-        self.add_blank_line();
-        self.append(types_printer);
-        writeln!(self, "}");
+                        // skip the blank lines at the top of the output
+                        if !line.is_empty() || !self.buffer.is_empty() {
+                            writeln!(self, line);
+                        }
+                    }
+                }
+
+                if !self.buffer.is_empty() {
+                    self.add_blank_line();
+                }
+                self.append(types_printer);
+            }
+        }
+    }
+
+    /// Returns the printed content, ending with exactly one newline
+    fn into_output(mut self) -> String {
+        let len = self.buffer.trim_end_matches('\n').len();
+        self.buffer.truncate(len);
+        self.buffer.push('\n');
         self.buffer
     }
 
+    /// Returns the keyword prefix of declarations, `export ` for ES modules
+    fn export(&self) -> &'static str {
+        match self.output_style {
+            OutputStyle::Global => "",
+            OutputStyle::Module => "export ",
+        }
+    }
+
     /// Write @lyft/bubble-client typescript definitions
     fn write_bubble_client_rpc(&mut self, ns: &'a Namespace, method_name: &'a str, rpc: &'a Rpc) {
-        self.print_comment(&rpc.md, true);
         let req = rpc.request_type.borrow();
         let req = self.rpc_type(req.as_str(), rpc.request_stream);
 
@@ -178,8 +594,7 @@ impl<'a> Printer<'a> {
 
         match HTTPOptions::from(&rpc.md.options) {
             Some(HTTPOptions {
-                path,
-                method,
+                bindings,
                 error_types,
             }) => {
                 let code_error_tuples = error_types
@@ -188,19 +603,23 @@ impl<'a> Printer<'a> {
                     .collect::<Vec<_>>()
                     .join(" | ");
 
-                writeln_and_indent!(self, "{}(", method.to_lowercase());
-                writeln!(self, "path: '{}',", path);
+                for HTTPBinding { path, method, .. } in bindings {
+                    self.print_comment(&rpc.md, true, &[]);
+                    writeln_and_indent!(self, "{}(", method.to_lowercase());
+                    writeln!(self, "path: '{}',", path);
 
-                writeln!(
-                    self,
-                    "handler: RouteHandler<{}, {}, {}>",
-                    req, resp, code_error_tuples,
-                );
-                outdent_and_writeln!(self, "): void");
+                    writeln!(
+                        self,
+                        "handler: RouteHandler<{}, {}, {}>",
+                        req, resp, code_error_tuples,
+                    );
+                    outdent_and_writeln!(self, "): void");
+                }
             }
             None => {
+                self.print_comment(&rpc.md, true, &[]);
                 writeln_and_indent!(self, "grpc(");
-                writeln!(self, "path: '/{}/{}',", ns.path.join("."), method_name);
+                writeln!(self, "path: '{}',", ns.grpc_path(method_name));
                 writeln!(
                     self,
                     "handler: RouteHandler<{}, {}, [code: number, body: string]>",
@@ -219,17 +638,19 @@ impl<'a> Printer<'a> {
         let resp = rpc.response_type.borrow();
         let resp = self.rpc_type(resp.as_str(), rpc.response_stream);
 
-        self.print_comment(&rpc.md, true);
-
         match HTTPOptions::from(&rpc.md.options) {
-            Some(HTTPOptions { path, method, .. }) => {
-                writeln_and_indent!(self, "{method}(", method = method.to_lowercase());
-                writeln!(self, "path: '{path}'", path = path);
-                outdent_and_writeln!(self, "): HTTPResource<{}, {}>", req, resp);
+            Some(HTTPOptions { bindings, .. }) => {
+                for HTTPBinding { path, method, .. } in bindings {
+                    self.print_comment(&rpc.md, true, &[]);
+                    writeln_and_indent!(self, "{method}(", method = method.to_lowercase());
+                    writeln!(self, "path: '{path}'", path = path);
+                    outdent_and_writeln!(self, "): HTTPResource<{}, {}>", req, resp);
+                }
             }
             None => {
+                self.print_comment(&rpc.md, true, &[]);
                 writeln_and_indent!(self, "grpc(");
-                writeln!(self, "path: '/{}/{}'", ns.path.join("."), method_name);
+                writeln!(self, "path: '{}'", ns.grpc_path(method_name));
                 outdent_and_writeln!(
                     self,
                     "): GRPCResource<{}, {}, [code: number, body: string]>): void",
@@ -241,11 +662,12 @@ impl<'a> Printer<'a> {
     }
 
     /// Write namespace typescript definitions
-    fn write_namespaces(&mut self, namespaces: &'a BTreeMap<String, Namespace>) {
+    fn write_namespaces(&mut self, namespaces: impl Iterator<Item = (&'a String, &'a Namespace)>) {
         for (name, ns) in namespaces {
-            writeln_and_indent!(self, "namespace {} {{", name);
+            let export = self.export();
+            writeln_and_indent!(self, "{}namespace {} {{", export, name);
             self.write_types(ns.types.iter());
-            self.write_namespaces(&ns.nested);
+            self.write_namespaces(ns.nested.iter());
             outdent_and_writeln!(self, "}");
         }
     }
@@ -255,14 +677,12 @@ impl<'a> Printer<'a> {
         for (name, t) in types {
             match t {
                 Type::Message(msg) => {
-                    self.print_comment(&msg.md, true);
+                    self.print_comment(&msg.md, true, &[]);
                     self.write_message(name, msg);
                 }
                 Type::Enum(e) => {
-                    self.print_comment(&e.md, true);
-                    writeln_and_indent!(self, "const enum {} {{", name);
-                    self.write_enum(e);
-                    outdent_and_writeln!(self, "}");
+                    self.print_comment(&e.md, true, &[]);
+                    self.write_enum(name, e);
                 }
             }
         }
@@ -275,6 +695,28 @@ impl<'a> Printer<'a> {
 
         for (name, field) in msg.fields.iter() {
             let type_name = field.type_name.borrow();
+            let field_name = self.config.field_case.convert(name);
+
+            // extra JSDoc tags printed after the field comment
+            let mut tags = Vec::new();
+
+            // message and enum types are absolute paths that are not mapped to a Typescript type
+            match type_name.strip_prefix('.') {
+                Some(path)
+                    if self.config.emit_see_links
+                        && !TYPE_MAPPING.contains_key(type_name.as_str()) =>
+                {
+                    tags.push(format!("@see {{@link {}}}", path));
+                }
+                _ => {}
+            };
+
+            match field.default_value() {
+                Some(value) if self.config.emit_defaults => {
+                    tags.push(format!("@default {}", value))
+                }
+                _ => {}
+            }
 
             let type_name = match type_name.as_str() {
                 ".google.protobuf.Any" => {
@@ -284,48 +726,74 @@ impl<'a> Printer<'a> {
                     generic_constraints.push(format!("{} = unknown", generic_name));
                     Cow::Owned(type_name)
                 }
-                name => self.get_type(name).into(),
+                name => self.get_field_type(name, field.js_type()).into(),
             };
 
-            printer.print_comment(&field.md, false);
+            printer.print_comment(&field.md, false, &tags);
+            printer.add_source_map_entry(&field.md);
             match (&field.key_type, &field.rule) {
                 (Some(key), _) => {
-                    writeln!(printer, "{}?: {{ [key: {}]: {} }}", name, key, type_name);
+                    writeln!(
+                        printer,
+                        "{}?: {{ [key: {}]: {} }}",
+                        field_name, key, type_name
+                    );
                 }
                 (None, Some(FieldRule::Repeated)) => {
-                    writeln!(printer, "{}?: Array<{}>", name, type_name);
+                    let array_type = self.config.array_style.format(&type_name);
+                    writeln!(printer, "{}?: {}", field_name, array_type);
                 }
-                (None, _) => writeln!(printer, "{}?: {}", name, type_name),
+                (None, _) => writeln!(printer, "{}?: {}", field_name, type_name),
             };
         }
 
-        match generic_constraints.len() {
-            0 => match msg.fields.len() {
-                0 => {
-                    self.includes.insert(EMPTY);
-                    writeln!(self, "interface {} extends Empty {{", msg_name)
-                }
-                _ => writeln!(self, "interface {} {{", msg_name),
-            },
-            _ => writeln!(
-                self,
-                "interface {}<{}> {{",
-                msg_name,
-                generic_constraints.join(",")
-            ),
+        let generics = match generic_constraints.len() {
+            0 => String::new(),
+            _ => format!("<{}>", generic_constraints.join(",")),
+        };
+
+        // the type extended by messages without fields
+        let empty_type = match (msg.fields.is_empty(), self.config.empty_message_style) {
+            (false, _) | (true, EmptyMessageStyle::Object) => None,
+            (true, EmptyMessageStyle::Empty) => {
+                self.includes.insert(EMPTY);
+                Some("Empty")
+            }
+            (true, EmptyMessageStyle::Record) => Some("Record<string, never>"),
+        };
+
+        let export = self.export();
+        self.add_source_map_entry(&msg.md);
+        match (self.config.message_style, empty_type) {
+            (MessageStyle::Interface, Some(empty_type)) => {
+                writeln!(
+                    self,
+                    "{}interface {} extends {} {{",
+                    export, msg_name, empty_type
+                )
+            }
+            (MessageStyle::Interface, None) => {
+                writeln!(self, "{}interface {}{} {{", export, msg_name, generics)
+            }
+            (MessageStyle::Type, Some(empty_type)) => {
+                writeln!(self, "{}type {} = {} & {{", export, msg_name, empty_type)
+            }
+            (MessageStyle::Type, None) => {
+                writeln!(self, "{}type {}{} = {{", export, msg_name, generics)
+            }
         }
 
         for (name, oneof) in msg.oneofs.iter() {
-            printer.print_comment(&oneof.md, false);
+            printer.print_comment(&oneof.md, false, &[]);
             writeln!(
                 printer,
                 "{}?: Extract<keyof {}, {}>",
-                name,
+                self.config.field_case.convert(name),
                 msg_name,
                 oneof
                     .values
                     .iter()
-                    .map(|v| format!("'{}'", v))
+                    .map(|v| format!("'{}'", self.config.field_case.convert(v)))
                     .collect::<Vec<_>>()
                     .join(" | ")
             );
@@ -336,17 +804,40 @@ impl<'a> Printer<'a> {
         writeln!(self, "}");
 
         if !msg.nested.is_empty() {
-            writeln_and_indent!(self, "namespace {} {{", msg_name);
+            writeln_and_indent!(self, "{}namespace {} {{", export, msg_name);
             self.write_types(msg.nested.iter());
             outdent_and_writeln!(self, "}");
         }
     }
 
     /// Write a Proto enum typescript definitions
-    fn write_enum(&mut self, e: &Enum) {
+    fn write_enum(&mut self, enum_name: &str, e: &Enum) {
+        let export = self.export();
+        self.add_source_map_entry(&e.md);
+        let keyword = match self.config.enum_style {
+            EnumStyle::ConstEnum => "const enum",
+            EnumStyle::Enum => "enum",
+            EnumStyle::UnionType => {
+                let values = match e.values.is_empty() {
+                    true => "never".to_string(),
+                    false => e
+                        .values
+                        .keys()
+                        .map(|v| format!("'{}'", v))
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                };
+
+                writeln!(self, "{}type {} = {}", export, enum_name, values);
+                return;
+            }
+        };
+
+        writeln_and_indent!(self, "{}{} {} {{", export, keyword, enum_name);
         for (name, value) in e.values.iter() {
             writeln!(self, "{} = {},", name, value);
         }
+        outdent_and_writeln!(self, "}");
     }
 
     /// create a copy of the current printer with a blank buffer
@@ -356,6 +847,8 @@ impl<'a> Printer<'a> {
             includes: HashSet::new(),
             config: self.config,
             indent,
+            output_style: self.output_style,
+            source_map: self.source_map.as_ref().map(|_| Vec::new()),
         }
     }
 
@@ -366,11 +859,32 @@ impl<'a> Printer<'a> {
 
     /// Append the other printer content to self
     fn append(&mut self, other: Printer) {
+        if let (Some(source_map), Some(other_source_map)) = (&mut self.source_map, other.source_map)
+        {
+            // the other printer lines start after the current content
+            let offset = self.buffer.matches('\n').count();
+            source_map.extend(other_source_map.into_iter().map(|entry| SourceMapEntry {
+                line: entry.line + offset,
+                ..entry
+            }));
+        }
+
         self.buffer.push_str(other.buffer.as_str())
     }
 
-    /// Print a JSDoc comment
-    fn print_comment(&mut self, md: &Metadata, include_link: bool) {
+    /// Record the source of the declaration printed on the next line, when the source map is enabled
+    fn add_source_map_entry(&mut self, md: &Metadata) {
+        if let Some(source_map) = &mut self.source_map {
+            source_map.push(SourceMapEntry {
+                line: self.buffer.matches('\n').count() + 1,
+                file_path: md.file_path.clone(),
+                source_line: md.line,
+            });
+        }
+    }
+
+    /// Print a JSDoc comment, followed by the given extra tags (e.g `@see {@link pb.Foo}`)
+    fn print_comment(&mut self, md: &Metadata, include_link: bool, tags: &[String]) {
         let mut lines: Vec<Cow<str>> = match md.comment.as_ref() {
             Some(cmt) => cmt
                 .text
@@ -395,16 +909,22 @@ impl<'a> Printer<'a> {
             lines.push(" @deprecated".into())
         }
 
+        for tag in tags {
+            lines.push(format!(" {}", tag).into());
+        }
+
         if include_link {
-            lines.push(
-                format!(
-                    " @link {url}/{path}#{line}",
+            let url = match &self.config.link_resolver {
+                Some(resolver) => resolver(&md.file_path, md.line),
+                None => format!(
+                    "{url}/{path}#{line}",
                     url = self.config.root_url,
                     path = md.file_path.to_str().unwrap(),
                     line = md.line
-                )
-                .into(),
-            );
+                ),
+            };
+
+            lines.push(format!(" @link {}", url).into());
         }
 
         if lines.is_empty() {
@@ -422,14 +942,19 @@ impl<'a> Printer<'a> {
 
     /// Helper function that returns the type or the mapped Typescript if it exists
     fn get_type<'b>(&mut self, name: impl Into<&'b str>) -> &'b str {
-        let name = name.into();
-        match TYPE_MAPPING.get(name) {
-            Some(t @ &"LongLike") => {
-                self.includes.insert(LONG_LIKE_TYPE);
-                t
-            }
-            Some(t) => t,
-            None => &name[1..],
+        let ts_type = map_type(name.into());
+        if ts_type == "LongLike" {
+            self.includes.insert(LONG_LIKE_TYPE);
+        }
+        ts_type
+    }
+
+    /// Helper function that returns the field type, 64-bit integers are mapped according to their jstype
+    fn get_field_type<'b>(&mut self, name: &'b str, js_type: Option<JsType>) -> &'b str {
+        match (TYPE_MAPPING.get(name), js_type) {
+            (Some(&"LongLike"), Some(JsType::String)) => "string",
+            (Some(&"LongLike"), Some(JsType::Number)) => "number",
+            _ => self.get_type(name),
         }
     }
 
@@ -444,18 +969,89 @@ impl<'a> Printer<'a> {
     }
 }
 
+/// Returns the Typescript type of the given proto type: scalars and well-known types are mapped to their
+/// Typescript equivalent, and message or enum types are printed as their fully qualified name.
+/// `google.protobuf.Any` is mapped to `AnyType`, with its default type parameter, where the printer
+/// names the parameter after the field. 64-bit integers are mapped to `LongLike`, the `jstype` field option
+/// isn't part of the type and is not applied.
+///
+/// # Example:
+///
+/// ```
+/// use prosecco::typescript::{serializer::PrintConfig, ts_type};
+///
+/// let config = PrintConfig::default();
+/// assert_eq!(ts_type("int64", &config), "LongLike");
+/// assert_eq!(ts_type(".google.protobuf.StringValue", &config), "string");
+/// assert_eq!(ts_type(".google.protobuf.Any", &config), "AnyType");
+/// assert_eq!(ts_type(".pb.hello.SayHelloRequest", &config), "pb.hello.SayHelloRequest");
+/// ```
+pub fn ts_type(proto_type: &str, _config: &PrintConfig) -> String {
+    match proto_type {
+        ".google.protobuf.Any" => String::from("AnyType"),
+        name => map_type(name).to_string(),
+    }
+}
+
+/// Returns the mapped Typescript type if it exists, or the type name without its leading "."
+fn map_type(name: &str) -> &str {
+    match TYPE_MAPPING.get(name) {
+        Some(t) => t,
+        None => name.strip_prefix('.').unwrap_or(name),
+    }
+}
+
+/// Add the top-level namespaces referenced by the fields of the given namespace, and by its rpcs if include_rpcs is set
+fn add_references(ns: &Namespace, include_rpcs: bool, references: &mut BTreeSet<String>) {
+    let mut add = |type_name: &str| match type_name.strip_prefix('.') {
+        Some(_) if TYPE_MAPPING.contains_key(type_name) => {}
+        Some(_) if type_name == ".google.protobuf.Any" => {}
+        Some(path) => {
+            let name = path.split('.').next().unwrap_or(path);
+            references.insert(name.to_string());
+        }
+        None => {}
+    };
+
+    fn add_types<'a>(types: impl Iterator<Item = &'a Type>, add: &mut impl FnMut(&str)) {
+        for t in types {
+            if let Type::Message(msg) = t {
+                for field in msg.fields.values() {
+                    add(field.type_name.borrow().as_str());
+                }
+                add_types(msg.nested.values(), add);
+            }
+        }
+    }
+
+    add_types(ns.types.values(), &mut add);
+
+    if include_rpcs {
+        for service in ns.services.values() {
+            for rpc in service.methods.values() {
+                add(rpc.request_type.borrow().as_str());
+                add(rpc.response_type.borrow().as_str());
+            }
+        }
+    }
+
+    for child in ns.nested.values() {
+        add_references(child, include_rpcs, references);
+    }
+}
+
 // Helper function that execute recursively for each rpc in a namespace
 fn for_each_rpc<'a, F>(ns: &'a Namespace, callback: &mut F)
 where
     F: FnMut(&'a Namespace, &'a str, &'a Rpc),
 {
-    for ns in ns.nested.values() {
-        for service in ns.services.values() {
-            for (method_name, rpc) in service.methods.iter() {
-                callback(ns, method_name, rpc)
-            }
+    for service in ns.services.values() {
+        for (method_name, rpc) in service.methods.iter() {
+            callback(ns, method_name, rpc)
         }
+    }
 
+    for ns in ns.nested.values() {
         for_each_rpc(ns, callback);
     }
 }
@@ -463,11 +1059,18 @@ where
 #[cfg(test)]
 mod tests {
     use crate::{
-        parser::test_util::parse_test_file,
-        typescript::serializer::{PrintConfig, Printer},
+        parser::{
+            test_util::{parse_test_file, virtual_fs},
+            Parser,
+        },
+        typescript::serializer::{
+            ArrayStyle, EmptyMessageStyle, EnumStyle, FieldCase, MessageStyle, OutputStyle,
+            PrintConfig, Printer,
+        },
     };
     use indoc::indoc;
     use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
 
     #[test]
     fn test_generate_typescript_definition() {
@@ -498,6 +1101,7 @@ mod tests {
             root_url: "https://github.com/lyft/idl/blob/master/protos".into(),
             print_bubble_client: true,
             print_network_client: true,
+            ..Default::default()
         };
 
         let printer = Printer::new(&config);
@@ -577,4 +1181,726 @@ mod tests {
 
         assert_eq!(output, result);
     }
+
+    #[test]
+    fn test_generate_package_less_typescript_definition() {
+        let root = parse_test_file(indoc! {r#"
+        message Foo {
+          Bar bar = 1;
+        }
+
+        message Bar {}
+        "#});
+
+        let config = PrintConfig {
+            root_url: "https://github.com/lyft/idl/blob/master/protos".into(),
+            print_bubble_client: true,
+            print_network_client: true,
+            ..Default::default()
+        };
+
+        let printer = Printer::new(&config);
+        let output = printer.into_string(&root);
+
+        let result = indoc! {r#"
+        declare global {
+          
+          interface Empty { _?: never }
+
+        
+          /**
+           * @link https://github.com/lyft/idl/blob/master/protos/test.proto#1
+           */
+          interface Foo {
+            bar?: Bar
+          }
+        
+          /**
+           * @link https://github.com/lyft/idl/blob/master/protos/test.proto#5
+           */
+          interface Bar extends Empty {
+          }
+        }
+        "#};
+
+        assert_eq!(output, result);
+    }
+
+    #[test]
+    fn test_package_less_service() {
+        let root = parse_test_file(indoc! {r#"
+        service HelloWorld {
+          rpc SayHello (SayHelloRequest) returns (SayHelloResponse) {}
+        }
+
+        message SayHelloRequest {}
+        message SayHelloResponse {}
+        "#});
+
+        let output = Printer::new(&PrintConfig::default()).into_string(&root);
+        assert!(output.contains("path: '/SayHello',"), "{}", output);
+        assert!(output.contains("path: '/SayHello'\n"), "{}", output);
+        assert!(!output.contains("//SayHello"), "{}", output);
+    }
+
+    #[test]
+    fn test_link_resolver() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message Greeting {}
+        "#});
+
+        let config = PrintConfig::builder()
+            .root_url("https://unused")
+            .link_resolver(|path, line| {
+                format!(
+                    "https://example.com/idl/blob/main/{}?plain=1#L{}",
+                    path.display(),
+                    line
+                )
+            })
+            .build();
+
+        let output = Printer::new(&config).into_string(&root);
+        assert!(
+            output.contains(" * @link https://example.com/idl/blob/main/test.proto?plain=1#L3\n"),
+            "{}",
+            output
+        );
+        assert!(!output.contains("https://unused"), "{}", output);
+    }
+
+    #[test]
+    fn test_message_style() {
+        let root = parse_test_file(indoc! {r#"
+        message Foo {
+          Bar bar = 1;
+        }
+
+        message Bar {}
+        "#});
+
+        for (message_style, expected) in [
+            (
+                MessageStyle::Interface,
+                indoc! {r#"
+                declare global {
+                  
+                  interface Empty { _?: never }
+
+                
+                  /**
+                   * @link /test.proto#1
+                   */
+                  interface Foo {
+                    bar?: Bar
+                  }
+                
+                  /**
+                   * @link /test.proto#5
+                   */
+                  interface Bar extends Empty {
+                  }
+                }
+                "#},
+            ),
+            (
+                MessageStyle::Type,
+                indoc! {r#"
+                declare global {
+                  
+                  interface Empty { _?: never }
+
+                
+                  /**
+                   * @link /test.proto#1
+                   */
+                  type Foo = {
+                    bar?: Bar
+                  }
+                
+                  /**
+                   * @link /test.proto#5
+                   */
+                  type Bar = Empty & {
+                  }
+                }
+                "#},
+            ),
+        ] {
+            let config = PrintConfig::builder().message_style(message_style).build();
+            let output = Printer::new(&config).into_string(&root);
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_disabled_clients_includes() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        service HelloWorld {
+          rpc LotsOfGreetings(stream SayHelloRequest) returns (stream SayHelloRequest) {}
+        }
+
+        message SayHelloRequest {
+          int64 id = 1;
+        }
+        "#});
+
+        let config = PrintConfig::builder()
+            .print_bubble_client(false)
+            .print_network_client(false)
+            .build();
+        let output = Printer::new(&config).into_string(&root);
+
+        assert!(!output.contains("rxjs"), "{}", output);
+        assert!(!output.contains("@lyft"), "{}", output);
+        assert!(output.contains("type LongLike"), "{}", output);
+
+        let config = PrintConfig::builder().print_network_client(false).build();
+        let output = Printer::new(&config).into_string(&root);
+        assert!(
+            output.contains("import { Observable } from 'rxjs'"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_module_output_style() {
+        let files = virtual_fs(&[
+            (
+                "hello.proto",
+                indoc! {r#"
+                package pb.hello;
+                import "money.proto";
+
+                message Item {
+                  enum Kind {
+                    UNKNOWN = 0;
+                  }
+
+                  int64 id = 1;
+                  acme.common.Money price = 2;
+                  Kind kind = 3;
+                }
+                "#},
+            ),
+            (
+                "money.proto",
+                "package acme.common;\nmessage Money { int64 cents = 1; }",
+            ),
+        ]);
+
+        let mut parser = Parser::with_virtual_fs(files);
+        parser
+            .parse_file(PathBuf::from("hello.proto"))
+            .expect("it should parse hello.proto");
+        let root = parser.build_root().expect("it should build root");
+
+        let config = PrintConfig::builder()
+            .link_resolver(|path, line| format!("{}#{}", path.display(), line))
+            .output_style(OutputStyle::Module)
+            .build();
+
+        let mut modules = Printer::new(&config).into_modules(&root);
+        assert_eq!(
+            modules.keys().collect::<Vec<_>>(),
+            vec!["acme", "pb"],
+            "there should be a module per top-level namespace"
+        );
+
+        let pb = indoc! {r#"
+        import { acme } from './acme'
+
+        type LongLike = number | BigInt | { toNumber(): number }
+
+        export namespace pb {
+          export namespace hello {
+
+            /**
+             * @link hello.proto#4
+             */
+            export interface Item {
+              id?: LongLike
+              price?: acme.common.Money
+              kind?: pb.hello.Item.Kind
+            }
+            export namespace Item {
+
+              /**
+               * @link hello.proto#5
+               */
+              export const enum Kind {
+                UNKNOWN = 0,
+              }
+            }
+          }
+        }
+        "#};
+
+        assert_eq!(modules.remove("pb").unwrap(), pb);
+
+        // the whole root is printed as a single module with into_string
+        let output = Printer::new(&config).into_string(&root);
+        assert!(output.starts_with("type LongLike"), "{}", output);
+        assert!(output.contains("export namespace acme {"), "{}", output);
+        assert!(output.contains("export namespace pb {"), "{}", output);
+        assert!(!output.contains("declare global"), "{}", output);
+        assert!(!output.contains("import"), "{}", output);
+    }
+
+    #[test]
+    fn test_source_map() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message Foo {
+          string name = 1;
+        }
+
+        enum Status {
+          UNKNOWN = 0;
+        }
+        "#});
+
+        let config = PrintConfig::builder().header("// header").build();
+        let (output, source_map) = Printer::new(&config).into_string_with_source_map(&root);
+        let output_lines = output.lines().collect::<Vec<_>>();
+
+        let sources = source_map
+            .iter()
+            .map(|entry| (output_lines[entry.line - 1].trim(), entry.source_line))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            sources,
+            vec![
+                ("interface Foo {", 3),
+                ("name?: string", 4),
+                ("const enum Status {", 7),
+            ]
+        );
+        assert!(source_map
+            .iter()
+            .all(|entry| entry.file_path.to_str() == Some("test.proto")));
+        assert_eq!(output, Printer::new(&config).into_string(&root));
+    }
+
+    #[test]
+    fn test_header() {
+        let root = parse_test_file("package pb.hello; message Foo { string name = 1; }");
+
+        for style in [OutputStyle::Global, OutputStyle::Module] {
+            let config = PrintConfig::builder()
+                .header("// GENERATED - DO NOT EDIT")
+                .output_style(style)
+                .build();
+
+            let output = Printer::new(&config).into_string(&root);
+            assert!(
+                output.starts_with("// GENERATED - DO NOT EDIT\n"),
+                "{}",
+                output
+            );
+            assert!(
+                output.ends_with("}\n") && !output.ends_with("\n\n"),
+                "{:?}",
+                output
+            );
+
+            let modules = Printer::new(&config).into_modules(&root);
+            assert!(modules["pb"].starts_with("// GENERATED - DO NOT EDIT\n"));
+            assert!(modules["pb"].ends_with("}\n"));
+        }
+
+        let root = parse_test_file("package pb.hello;");
+        let config = PrintConfig::builder()
+            .header("// banner\n")
+            .output_style(OutputStyle::Module)
+            .build();
+        assert_eq!(Printer::new(&config).into_string(&root), "// banner\n");
+    }
+
+    #[test]
+    fn test_empty_message_style() {
+        let root = parse_test_file("message Foo {}");
+
+        for (empty_message_style, message_style, expected) in [
+            (
+                EmptyMessageStyle::Empty,
+                MessageStyle::Interface,
+                "interface Foo extends Empty {",
+            ),
+            (
+                EmptyMessageStyle::Record,
+                MessageStyle::Interface,
+                "interface Foo extends Record<string, never> {",
+            ),
+            (
+                EmptyMessageStyle::Record,
+                MessageStyle::Type,
+                "type Foo = Record<string, never> & {",
+            ),
+            (
+                EmptyMessageStyle::Object,
+                MessageStyle::Interface,
+                "interface Foo {",
+            ),
+            (
+                EmptyMessageStyle::Object,
+                MessageStyle::Type,
+                "type Foo = {",
+            ),
+        ] {
+            let config = PrintConfig::builder()
+                .empty_message_style(empty_message_style)
+                .message_style(message_style)
+                .build();
+            let output = Printer::new(&config).into_string(&root);
+
+            assert!(output.contains(expected), "{}", output);
+            assert_eq!(
+                output.contains("interface Empty"),
+                empty_message_style == EmptyMessageStyle::Empty,
+                "{}",
+                output
+            );
+        }
+    }
+
+    #[test]
+    fn test_array_style() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message SayHelloResponses {
+          repeated string names = 1;
+        }
+        "#});
+
+        for (array_style, expected) in [
+            (ArrayStyle::Array, "names?: Array<string>"),
+            (ArrayStyle::ReadonlyArray, "names?: ReadonlyArray<string>"),
+            (ArrayStyle::Brackets, "names?: string[]"),
+        ] {
+            let config = PrintConfig {
+                array_style,
+                ..Default::default()
+            };
+
+            let output = Printer::new(&config).into_string(&root);
+            assert!(output.contains(expected), "{}", output);
+        }
+
+        assert_eq!(
+            ArrayStyle::Brackets.format("globalThis.Date | string"),
+            "(globalThis.Date | string)[]"
+        );
+    }
+
+    #[test]
+    fn test_nested_enum_reference() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message Greeting {
+          enum Status {
+            UNKNOWN = 0;
+          }
+
+          Status status = 1;
+        }
+
+        message Other {
+          Greeting.Status status = 1;
+        }
+        "#});
+
+        let config = PrintConfig {
+            root_url: "https://github.com/lyft/idl/blob/master/protos".into(),
+            ..Default::default()
+        };
+
+        let output = Printer::new(&config).into_string(&root);
+
+        let result = indoc! {r#"
+        declare global {
+        
+          namespace pb {
+            namespace hello {
+        
+              /**
+               * @link https://github.com/lyft/idl/blob/master/protos/test.proto#3
+               */
+              interface Greeting {
+                status?: pb.hello.Greeting.Status
+              }
+              namespace Greeting {
+        
+                /**
+                 * @link https://github.com/lyft/idl/blob/master/protos/test.proto#4
+                 */
+                const enum Status {
+                  UNKNOWN = 0,
+                }
+              }
+        
+              /**
+               * @link https://github.com/lyft/idl/blob/master/protos/test.proto#11
+               */
+              interface Other {
+                status?: pb.hello.Greeting.Status
+              }
+            }
+          }
+        }
+        "#};
+
+        assert_eq!(output, result);
+    }
+
+    #[test]
+    fn test_self_referential_message() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.x;
+
+        message Node {
+          Node next = 1;
+          repeated Node children = 2;
+          map<string, Node> by_name = 3;
+        }
+        "#});
+
+        let config = PrintConfig {
+            root_url: "https://github.com/lyft/idl/blob/master/protos".into(),
+            ..Default::default()
+        };
+
+        let output = Printer::new(&config).into_string(&root);
+
+        let result = indoc! {r#"
+        declare global {
+        
+          namespace pb {
+            namespace x {
+        
+              /**
+               * @link https://github.com/lyft/idl/blob/master/protos/test.proto#3
+               */
+              interface Node {
+                next?: pb.x.Node
+                children?: Array<pb.x.Node>
+                by_name?: { [key: string]: pb.x.Node }
+              }
+            }
+          }
+        }
+        "#};
+
+        assert_eq!(output, result);
+    }
+
+    #[test]
+    fn test_enum_style() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        enum Status {
+          UNKNOWN = 0;
+          STARTED = 1;
+        }
+        "#});
+
+        for (enum_style, expected) in [
+            (
+                EnumStyle::ConstEnum,
+                indoc! {r#"
+                declare global {
+                
+                  namespace pb {
+                    namespace hello {
+                
+                      /**
+                       * @link /test.proto#3
+                       */
+                      const enum Status {
+                        UNKNOWN = 0,
+                        STARTED = 1,
+                      }
+                    }
+                  }
+                }
+                "#},
+            ),
+            (
+                EnumStyle::Enum,
+                indoc! {r#"
+                declare global {
+                
+                  namespace pb {
+                    namespace hello {
+                
+                      /**
+                       * @link /test.proto#3
+                       */
+                      enum Status {
+                        UNKNOWN = 0,
+                        STARTED = 1,
+                      }
+                    }
+                  }
+                }
+                "#},
+            ),
+            (
+                EnumStyle::UnionType,
+                indoc! {r#"
+                declare global {
+                
+                  namespace pb {
+                    namespace hello {
+                
+                      /**
+                       * @link /test.proto#3
+                       */
+                      type Status = 'UNKNOWN' | 'STARTED'
+                    }
+                  }
+                }
+                "#},
+            ),
+        ] {
+            let config = PrintConfig::builder().enum_style(enum_style).build();
+            let output = Printer::new(&config).into_string(&root);
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_field_case() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message Greeting {
+          string snake_case_field = 1;
+          oneof the_choice {
+            string first_choice = 2;
+          }
+        }
+        "#});
+
+        for (field_case, expected) in [
+            (
+                FieldCase::AsIs,
+                [
+                    "snake_case_field?: string",
+                    "the_choice?: Extract<keyof Greeting, 'first_choice'>",
+                ],
+            ),
+            (
+                FieldCase::Camel,
+                [
+                    "snakeCaseField?: string",
+                    "theChoice?: Extract<keyof Greeting, 'firstChoice'>",
+                ],
+            ),
+        ] {
+            let config = PrintConfig::builder().field_case(field_case).build();
+            let output = Printer::new(&config).into_string(&root);
+            for line in expected.iter() {
+                assert!(output.contains(line), "{}", output);
+            }
+        }
+    }
+
+    #[test]
+    fn test_emit_see_links() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message Greeting {
+          string name = 1;
+          Status status = 2;
+          Other other = 3;
+        }
+
+        enum Status {
+          OK = 0;
+        }
+
+        message Other {}
+        "#});
+
+        let config = PrintConfig::builder().emit_see_links(true).build();
+        let output = Printer::new(&config).into_string(&root);
+        assert!(
+            output.contains(" * @see {@link pb.hello.Status}\n"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains(" * @see {@link pb.hello.Other}\n"),
+            "{}",
+            output
+        );
+        assert_eq!(output.matches("@see").count(), 2, "{}", output);
+
+        let output = Printer::new(&PrintConfig::default()).into_string(&root);
+        assert!(!output.contains("@see"), "{}", output);
+    }
+
+    #[test]
+    fn test_emit_defaults() {
+        let root = parse_test_file(indoc! {r#"
+        syntax = "proto2";
+        package pb.hello;
+
+        message Greeting {
+          optional Status status = 1 [default = OK];
+          optional int32 count = 2 [default = 10];
+          optional string name = 3;
+        }
+
+        enum Status {
+          UNKNOWN = 0;
+          OK = 1;
+        }
+        "#});
+
+        let config = PrintConfig::builder().emit_defaults(true).build();
+        let output = Printer::new(&config).into_string(&root);
+        assert!(output.contains(" * @default OK\n"), "{}", output);
+        assert!(output.contains(" * @default 10\n"), "{}", output);
+        assert_eq!(output.matches("@default").count(), 2, "{}", output);
+
+        let output = Printer::new(&PrintConfig::default()).into_string(&root);
+        assert!(!output.contains("@default"), "{}", output);
+    }
+
+    #[test]
+    fn test_jstype() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message Greeting {
+          int64 as_string = 1 [jstype = JS_STRING];
+          uint64 as_number = 2 [deprecated = true, jstype = JS_NUMBER];
+          int64 as_long = 3 [jstype = JS_NORMAL];
+          int32 count = 4 [jstype = JS_STRING];
+        }
+        "#});
+
+        let output = Printer::new(&PrintConfig::default()).into_string(&root);
+        for line in [
+            "as_string?: string",
+            "as_number?: number",
+            "as_long?: LongLike",
+            "count?: number",
+        ] {
+            assert!(output.contains(line), "{}", output);
+        }
+    }
 }
@@ -0,0 +1,158 @@
+//! Extract the common [protoc-gen-validate] rules of a field into structured [FieldConstraints]
+//!
+//! # Example:
+//!
+//! ```proto
+//! message User {
+//!   string name = 1 [(validate.rules).string = {min_len: 1, max_len: 64}];
+//!   int32 age = 2 [(validate.rules).int32.gte = 18];
+//! }
+//! ```
+//!
+//! [protoc-gen-validate]: https://github.com/envoyproxy/protoc-gen-validate
+
+use crate::metadata::ProtoOption;
+
+/// The option name used by protoc-gen-validate
+const VALIDATE_RULES: &str = "validate.rules";
+
+/// The validation constraints of a field, extracted from its `(validate.rules)` options
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FieldConstraints {
+    /// The message field must be set (`message.required`)
+    pub required: bool,
+
+    /// The number must be greater than this value
+    pub gt: Option<f64>,
+
+    /// The number must be greater than or equal to this value
+    pub gte: Option<f64>,
+
+    /// The number must be less than this value
+    pub lt: Option<f64>,
+
+    /// The number must be less than or equal to this value
+    pub lte: Option<f64>,
+
+    /// The minimum length of a string, in characters
+    pub min_len: Option<u64>,
+
+    /// The maximum length of a string, in characters
+    pub max_len: Option<u64>,
+
+    /// The regular expression a string must match
+    pub pattern: Option<String>,
+
+    /// The well-known format of a string (e.g email, uri, uuid)
+    pub format: Option<String>,
+
+    /// The minimum number of items of a repeated field
+    pub min_items: Option<u64>,
+
+    /// The maximum number of items of a repeated field
+    pub max_items: Option<u64>,
+}
+
+impl FieldConstraints {
+    /// Returns the constraints defined by the given options, or None if there is no `(validate.rules)` option.
+    /// Rules are either set one at a time (e.g `(validate.rules).string.pattern = "..."`),
+    /// or as a block (e.g `(validate.rules).int32 = {gte: 1, lt: 10}`). Unsupported rules are ignored
+    pub fn from_options(options: &[ProtoOption]) -> Option<Self> {
+        let mut constraints = None;
+
+        for option in options {
+            let mut values = option.iter().map(String::as_str).peekable();
+
+            while let Some(value) = values.next() {
+                if value != VALIDATE_RULES {
+                    continue;
+                }
+
+                let constraints = constraints.get_or_insert_with(Self::default);
+
+                // the rules, as (path, value) entries, e.g (string.pattern, "...") or (int32.gte, 1)
+                while let Some(path) = values.next_if(|v| *v != VALIDATE_RULES) {
+                    let value = match values.next_if(|v| *v != VALIDATE_RULES) {
+                        Some(value) => value,
+                        None => break,
+                    };
+
+                    if let Some((rule_type, key)) = path.split_once('.') {
+                        constraints.set(rule_type, key, value);
+                    }
+                }
+            }
+        }
+
+        constraints
+    }
+
+    /// Set the constraint matching the given rule type (e.g string) and key (e.g min_len)
+    fn set(&mut self, rule_type: &str, key: &str, value: &str) {
+        match (rule_type, key) {
+            ("message", "required") => self.required = value == "true",
+            ("repeated", "min_items") => self.min_items = value.parse().ok(),
+            ("repeated", "max_items") => self.max_items = value.parse().ok(),
+            ("string" | "bytes", "min_len") => self.min_len = value.parse().ok(),
+            ("string" | "bytes", "max_len") => self.max_len = value.parse().ok(),
+            ("string" | "bytes", "pattern") => self.pattern = Some(value.to_string()),
+            (
+                "string",
+                "email" | "hostname" | "ip" | "ipv4" | "ipv6" | "uri" | "uri_ref" | "uuid",
+            ) if value == "true" => self.format = Some(key.to_string()),
+            (_, "gt") => self.gt = value.parse().ok(),
+            (_, "gte") => self.gte = value.parse().ok(),
+            (_, "lt") => self.lt = value.parse().ok(),
+            (_, "lte") => self.lte = value.parse().ok(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldConstraints;
+    use crate::parser::test_util::parse_test_file;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_field_constraints() {
+        let root = parse_test_file(indoc! {r#"
+        package pb.hello;
+
+        message User {
+          string name = 1 [(validate.rules).string.pattern = "^[a-z]+$", (validate.rules).string = {min_len: 1, max_len: 64}];
+          int32 age = 2 [(validate.rules).int32 = {gte: 18, lt: 150}, deprecated = true];
+          string email = 3 [(validate.rules).string.email = true];
+          string nickname = 4;
+        }
+        "#});
+
+        let msg = root.lookup_message("pb.hello.User").unwrap();
+        let constraints = |name: &str| msg.fields[name].constraints();
+
+        assert_eq!(
+            constraints("name"),
+            Some(FieldConstraints {
+                pattern: Some("^[a-z]+$".to_string()),
+                min_len: Some(1),
+                max_len: Some(64),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            constraints("age"),
+            Some(FieldConstraints {
+                gte: Some(18.0),
+                lt: Some(150.0),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            constraints("email").and_then(|c| c.format),
+            Some("email".to_string())
+        );
+        assert_eq!(constraints("nickname"), None);
+    }
+}
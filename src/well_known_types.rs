@@ -0,0 +1,113 @@
+//! Definitions of the well-known `google.protobuf.*` types, used when their files are not found on disk.
+//!
+//! The definitions only contain the types and their fields, see [crate::parser::Parser::register_builtin_types]
+
+use phf::phf_map;
+
+/// A map of import path => content of the well-known types files
+static WELL_KNOWN_FILES: phf::Map<&'static str, &'static str> = phf_map! {
+    "google/protobuf/any.proto" => r#"
+        syntax = "proto3";
+        package google.protobuf;
+
+        message Any {
+          string type_url = 1;
+          bytes value = 2;
+        }
+    "#,
+    "google/protobuf/duration.proto" => r#"
+        syntax = "proto3";
+        package google.protobuf;
+
+        message Duration {
+          int64 seconds = 1;
+          int32 nanos = 2;
+        }
+    "#,
+    "google/protobuf/empty.proto" => r#"
+        syntax = "proto3";
+        package google.protobuf;
+
+        message Empty {}
+    "#,
+    "google/protobuf/field_mask.proto" => r#"
+        syntax = "proto3";
+        package google.protobuf;
+
+        message FieldMask {
+          repeated string paths = 1;
+        }
+    "#,
+    "google/protobuf/struct.proto" => r#"
+        syntax = "proto3";
+        package google.protobuf;
+
+        message Struct {
+          map<string, Value> fields = 1;
+        }
+
+        message Value {
+          oneof kind {
+            NullValue null_value = 1;
+            double number_value = 2;
+            string string_value = 3;
+            bool bool_value = 4;
+            Struct struct_value = 5;
+            ListValue list_value = 6;
+          }
+        }
+
+        enum NullValue {
+          NULL_VALUE = 0;
+        }
+
+        message ListValue {
+          repeated Value values = 1;
+        }
+    "#,
+    "google/protobuf/timestamp.proto" => r#"
+        syntax = "proto3";
+        package google.protobuf;
+
+        message Timestamp {
+          int64 seconds = 1;
+          int32 nanos = 2;
+        }
+    "#,
+    "google/protobuf/wrappers.proto" => r#"
+        syntax = "proto3";
+        package google.protobuf;
+
+        message DoubleValue { double value = 1; }
+        message FloatValue { float value = 1; }
+        message Int64Value { int64 value = 1; }
+        message UInt64Value { uint64 value = 1; }
+        message Int32Value { int32 value = 1; }
+        message UInt32Value { uint32 value = 1; }
+        message BoolValue { bool value = 1; }
+        message StringValue { string value = 1; }
+        message BytesValue { bytes value = 1; }
+    "#,
+};
+
+/// Returns the content of the well-known types file imported with the given path (e.g google/protobuf/timestamp.proto)
+pub fn well_known_file(import_path: &str) -> Option<&'static str> {
+    WELL_KNOWN_FILES.get(import_path).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WELL_KNOWN_FILES;
+    use crate::file_parser::FileParser;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_well_known_files_parse() {
+        for (path, content) in WELL_KNOWN_FILES.entries() {
+            let ns = FileParser::new(PathBuf::from(path), content.chars())
+                .parse()
+                .unwrap_or_else(|error| panic!("{} should parse: {:?}", path, error));
+            assert_eq!(ns.path, vec!["google", "protobuf"], "{}", path);
+        }
+    }
+}